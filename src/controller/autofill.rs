@@ -0,0 +1,334 @@
+//! Rules for filling a cycle from whatever the player is carrying.
+//!
+//! Rules live in the `[AutoFill]` section of the settings file, one per key.
+//! The key is the rule's name and the value is a list of `field=value` pairs
+//! separated by spaces, for example:
+//!
+//! ```ini
+//! [AutoFill]
+//! HealthPotions = slot=utility kind=potion:health sort=strength limit=5
+//! Destruction = slot=left kind=spell:destruction sort=name mode=replace
+//! ```
+//!
+//! Fields:
+//! - `slot`: required; one of `power`, `utility`, `left`, or `right`.
+//! - `kind`: required; a comma-separated list of categories, each with an
+//!   optional subtype after a colon. See `KindMatch` for the list.
+//! - `sort`: optional; `name`, `strength`, or `count`. Strongest and most
+//!   numerous go first. Without it, items keep the order the game lists them in.
+//! - `limit`: optional; the most items to add.
+//! - `mode`: optional; `append` (the default) adds to the cycle, and
+//!   `replace` empties the cycle first.
+
+use eyre::{eyre, Result};
+
+use super::keys::CycleSlot;
+use crate::data::magic::School;
+use crate::data::potion::PotionType;
+use crate::data::{BaseType, HudItem};
+
+/// One auto-fill rule, parsed from the settings file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AutoFillRule {
+    /// The rule's name; its key in the settings file.
+    pub name: String,
+    /// The cycle to fill.
+    pub slot: CycleSlot,
+    /// An item is included if it matches any of these.
+    pub kinds: Vec<KindMatch>,
+    /// How to order the matching items.
+    pub sort: AutoFillSort,
+    /// The most items to add, if limited.
+    pub limit: Option<usize>,
+    /// Empty the cycle before filling it.
+    pub replace: bool,
+}
+
+/// The categories a rule can select. Subtypes are optional.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KindMatch {
+    /// `ammo`
+    Ammo,
+    /// `armor`
+    Armor,
+    /// `food`
+    Food,
+    /// `light`
+    Light,
+    /// `potion`, `potion:health`, `potion:magicka`, `potion:stamina`,
+    /// `potion:poison`, or `potion:resist`
+    Potion(Option<PotionKind>),
+    /// `power`
+    Power,
+    /// `scroll`
+    Scroll,
+    /// `shout`
+    Shout,
+    /// `spell` or `spell:<school>`, e.g. `spell:destruction`
+    Spell(Option<School>),
+    /// `weapon`, `weapon:onehanded`, or `weapon:twohanded`
+    Weapon(Option<bool>),
+}
+
+/// Potion subtypes a rule can ask for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PotionKind {
+    Health,
+    Magicka,
+    Stamina,
+    Poison,
+    Resist,
+}
+
+/// How to order items chosen by a rule.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum AutoFillSort {
+    /// Leave items in the order the game gave them to us.
+    #[default]
+    None,
+    /// Alphabetical by name.
+    Name,
+    /// Strongest first.
+    Strength,
+    /// Most numerous first.
+    Count,
+}
+
+impl AutoFillRule {
+    /// Parse a rule from its name and settings-file value.
+    pub fn parse(name: &str, value: &str) -> Result<Self> {
+        let mut slot = None;
+        let mut kinds = Vec::new();
+        let mut sort = AutoFillSort::None;
+        let mut limit = None;
+        let mut replace = false;
+
+        for pair in value.split_whitespace() {
+            let Some((field, setting)) = pair.split_once('=') else {
+                return Err(eyre!("expected field=value but found '{pair}'"));
+            };
+            let setting = setting.to_lowercase();
+            match field.to_lowercase().as_str() {
+                "slot" => {
+                    let Ok(parsed) = setting.parse::<CycleSlot>() else {
+                        return Err(eyre!("unknown cycle '{setting}'"));
+                    };
+                    slot = Some(parsed);
+                }
+                "kind" => {
+                    for kind in setting.split(',').filter(|xs| !xs.is_empty()) {
+                        kinds.push(KindMatch::parse(kind)?);
+                    }
+                }
+                "sort" => {
+                    sort = match setting.as_str() {
+                        "none" => AutoFillSort::None,
+                        "name" => AutoFillSort::Name,
+                        "strength" => AutoFillSort::Strength,
+                        "count" => AutoFillSort::Count,
+                        _ => return Err(eyre!("unknown sort '{setting}'")),
+                    }
+                }
+                "limit" => {
+                    let Ok(parsed) = setting.parse::<usize>() else {
+                        return Err(eyre!("limit must be a number but found '{setting}'"));
+                    };
+                    limit = Some(parsed);
+                }
+                "mode" => {
+                    replace = match setting.as_str() {
+                        "append" => false,
+                        "replace" => true,
+                        _ => return Err(eyre!("unknown mode '{setting}'")),
+                    }
+                }
+                _ => return Err(eyre!("unknown field '{field}'")),
+            }
+        }
+
+        let Some(slot) = slot else {
+            return Err(eyre!("the rule does not say which cycle to fill"));
+        };
+        if kinds.is_empty() {
+            return Err(eyre!("the rule does not say what kind of items to use"));
+        }
+
+        Ok(Self {
+            name: name.to_string(),
+            slot,
+            kinds,
+            sort,
+            limit,
+            replace,
+        })
+    }
+
+    /// Does this rule want the given item?
+    pub fn matches(&self, item: &HudItem) -> bool {
+        self.kinds.iter().any(|xs| xs.matches(item.kind()))
+    }
+
+    /// Choose and order form specs from a list of candidate items paired
+    /// with their strengths.
+    pub fn select(&self, mut candidates: Vec<(HudItem, f32)>) -> Vec<String> {
+        candidates.retain(|(item, _)| self.matches(item));
+        match self.sort {
+            AutoFillSort::None => {}
+            AutoFillSort::Name => candidates.sort_by_key(|(item, _)| item.name().to_lowercase()),
+            AutoFillSort::Strength => candidates.sort_by(|a, b| b.1.total_cmp(&a.1)),
+            AutoFillSort::Count => {
+                candidates.sort_by_key(|(item, _)| std::cmp::Reverse(item.count()))
+            }
+        }
+        let limit = self.limit.unwrap_or(candidates.len());
+        candidates
+            .into_iter()
+            .take(limit)
+            .map(|(item, _)| item.form_string())
+            .collect()
+    }
+}
+
+impl KindMatch {
+    fn parse(value: &str) -> Result<Self> {
+        let (category, subtype) = match value.split_once(':') {
+            Some((category, subtype)) => (category, Some(subtype)),
+            None => (value, None),
+        };
+
+        let kind = match (category, subtype) {
+            ("ammo", None) => KindMatch::Ammo,
+            ("armor", None) => KindMatch::Armor,
+            ("food", None) => KindMatch::Food,
+            ("light", None) => KindMatch::Light,
+            ("power", None) => KindMatch::Power,
+            ("scroll", None) => KindMatch::Scroll,
+            ("shout", None) => KindMatch::Shout,
+            ("potion", None) => KindMatch::Potion(None),
+            ("potion", Some(sub)) => KindMatch::Potion(Some(match sub {
+                "health" => PotionKind::Health,
+                "magicka" => PotionKind::Magicka,
+                "stamina" => PotionKind::Stamina,
+                "poison" => PotionKind::Poison,
+                "resist" => PotionKind::Resist,
+                _ => return Err(eyre!("unknown potion type '{sub}'")),
+            })),
+            ("spell", None) => KindMatch::Spell(None),
+            ("spell", Some(sub)) => KindMatch::Spell(Some(match sub {
+                "alteration" => School::Alteration,
+                "conjuration" => School::Conjuration,
+                "destruction" => School::Destruction,
+                "illusion" => School::Illusion,
+                "restoration" => School::Restoration,
+                _ => return Err(eyre!("unknown magic school '{sub}'")),
+            })),
+            ("weapon", None) => KindMatch::Weapon(None),
+            ("weapon", Some("onehanded")) => KindMatch::Weapon(Some(false)),
+            ("weapon", Some("twohanded")) => KindMatch::Weapon(Some(true)),
+            _ => return Err(eyre!("unknown kind '{value}'")),
+        };
+        Ok(kind)
+    }
+
+    fn matches(&self, kind: &BaseType) -> bool {
+        match (self, kind) {
+            (KindMatch::Ammo, BaseType::Ammo(_)) => true,
+            (KindMatch::Armor, BaseType::Armor(_)) => true,
+            (KindMatch::Food, BaseType::Food(_)) => true,
+            (KindMatch::Light, BaseType::Light(_)) => true,
+            (KindMatch::Power, BaseType::Power(_)) => true,
+            (KindMatch::Scroll, BaseType::Scroll(_)) => true,
+            (KindMatch::Shout, BaseType::Shout(_)) => true,
+            (KindMatch::Potion(None), BaseType::Potion(_)) => true,
+            (KindMatch::Potion(Some(wanted)), BaseType::Potion(t)) => matches!(
+                (wanted, t),
+                (PotionKind::Health, PotionType::Health)
+                    | (PotionKind::Magicka, PotionType::Magicka)
+                    | (PotionKind::Stamina, PotionType::Stamina)
+                    | (PotionKind::Poison, PotionType::Poison)
+                    | (PotionKind::Resist, PotionType::Resist(_))
+            ),
+            (KindMatch::Spell(None), BaseType::Spell(_)) => true,
+            (KindMatch::Spell(Some(school)), BaseType::Spell(t)) => t.school() == school,
+            (KindMatch::Weapon(None), BaseType::Weapon(_)) => true,
+            (KindMatch::Weapon(Some(twohanded)), BaseType::Weapon(_)) => {
+                kind.is_two_handed() == *twohanded
+            }
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn potion(name: &str, spec: &str, count: u32, kind: PotionType) -> HudItem {
+        HudItem::preclassified(
+            name.to_string(),
+            spec.to_string(),
+            count,
+            BaseType::Potion(kind),
+        )
+    }
+
+    #[test]
+    fn parsing_rules() {
+        let rule = AutoFillRule::parse(
+            "HealthPotions",
+            "slot=utility kind=potion:health,food sort=strength limit=3",
+        )
+        .expect("this rule should parse");
+        assert_eq!(rule.slot, CycleSlot::Utility);
+        assert_eq!(
+            rule.kinds,
+            vec![KindMatch::Potion(Some(PotionKind::Health)), KindMatch::Food]
+        );
+        assert_eq!(rule.sort, AutoFillSort::Strength);
+        assert_eq!(rule.limit, Some(3));
+        assert!(!rule.replace);
+
+        let rule = AutoFillRule::parse("Fire", "slot=LEFT kind=spell:destruction mode=replace")
+            .expect("this rule should parse");
+        assert_eq!(
+            rule.kinds,
+            vec![KindMatch::Spell(Some(School::Destruction))]
+        );
+        assert!(rule.replace);
+
+        assert!(AutoFillRule::parse("NoSlot", "kind=food").is_err());
+        assert!(AutoFillRule::parse("NoKind", "slot=utility").is_err());
+        assert!(AutoFillRule::parse("Bad", "slot=utility kind=potion:fancy").is_err());
+        assert!(AutoFillRule::parse("Bad", "slot=utility kind=food limit=lots").is_err());
+        assert!(AutoFillRule::parse("Bad", "slot=utility kind=food frobnicate").is_err());
+    }
+
+    #[test]
+    fn selecting_items() {
+        let rule = AutoFillRule::parse("Health", "slot=utility kind=potion:health sort=strength")
+            .expect("this rule should parse");
+        let candidates = vec![
+            (potion("Minor", "minor", 5, PotionType::Health), 25.0),
+            (potion("Blue", "blue", 5, PotionType::Magicka), 100.0),
+            (potion("Ultimate", "ultimate", 1, PotionType::Health), 500.0),
+            (potion("Plain", "plain", 2, PotionType::Health), 50.0),
+        ];
+        assert_eq!(
+            rule.select(candidates.clone()),
+            vec!["ultimate", "plain", "minor"]
+        );
+
+        let rule = AutoFillRule::parse("Potions", "slot=utility kind=potion sort=count limit=2")
+            .expect("this rule should parse");
+        let chosen = rule.select(candidates.clone());
+        assert_eq!(chosen.len(), 2);
+        assert!(chosen.iter().all(|xs| xs == "minor" || xs == "blue"));
+
+        let rule = AutoFillRule::parse("Potions", "slot=utility kind=potion sort=name")
+            .expect("this rule should parse");
+        assert_eq!(
+            rule.select(candidates),
+            vec!["blue", "minor", "plain", "ultimate"]
+        );
+    }
+}
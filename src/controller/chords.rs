@@ -0,0 +1,409 @@
+//! Key chords and sequences as extra bindings for HUD actions.
+//!
+//! Bindings live in the `[Chords]` section of the settings file, one per
+//! action. The key is the action's name and the value is a list of key codes,
+//! the same numbers the hotkey settings use. Join keys with `+` for a chord,
+//! held down together, or with `>` for a sequence, tapped one after another:
+//!
+//! ```ini
+//! [Chords]
+//! Undo = 29+44
+//! EquipSetSwap = 42+29+18
+//! ShowHide = 56>56
+//! ```
+//!
+//! Chords and sequences use two or three keys. Each tap in a sequence must
+//! come within `uKeySequenceMillis` of the one before it. Keys that are
+//! already hotkeys still do their usual job when tapped, so sequences work
+//! best with keys that aren't.
+//!
+//! Actions: `power`, `utility`, `left`, `right`, `activate`, `equipment`,
+//! `unequiphands`, `showhide`, `refreshlayout`, `undo`, `redo`, and
+//! `equipsetswap`.
+
+use std::fmt::Display;
+use std::time::{Duration, Instant};
+
+use eyre::{eyre, Result};
+
+use super::keys::KeyState;
+use crate::plugin::Action;
+
+/// The most keys a chord or sequence may use.
+const MAX_COMBO_KEYS: usize = 3;
+
+/// Keys that together make up one binding.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Combo {
+    /// Keys held down together, sorted so order doesn't matter.
+    Chord(Vec<u32>),
+    /// Keys tapped in this order.
+    Sequence(Vec<u32>),
+}
+
+impl Combo {
+    pub fn parse(value: &str) -> Result<Self> {
+        let value = value.trim();
+        let (is_chord, parts): (bool, Vec<&str>) = match (value.contains('+'), value.contains('>'))
+        {
+            (true, false) => (true, value.split('+').collect()),
+            (false, true) => (false, value.split('>').collect()),
+            (true, true) => return Err(eyre!("'{value}' mixes a chord and a sequence")),
+            (false, false) => {
+                return Err(eyre!(
+                    "'{value}' needs keys joined by + for a chord or > for a sequence"
+                ))
+            }
+        };
+        let mut keys = Vec::new();
+        for part in parts {
+            let Ok(key) = part.trim().parse::<u32>() else {
+                return Err(eyre!("'{}' is not a key code", part.trim()));
+            };
+            keys.push(key);
+        }
+        if keys.len() > MAX_COMBO_KEYS {
+            return Err(eyre!("'{value}' uses more than {MAX_COMBO_KEYS} keys"));
+        }
+
+        if is_chord {
+            keys.sort_unstable();
+            keys.dedup();
+            if keys.len() < 2 {
+                return Err(eyre!("the chord '{value}' needs two different keys"));
+            }
+            Ok(Combo::Chord(keys))
+        } else {
+            Ok(Combo::Sequence(keys))
+        }
+    }
+
+    pub fn keys(&self) -> &[u32] {
+        match self {
+            Combo::Chord(keys) => keys,
+            Combo::Sequence(keys) => keys,
+        }
+    }
+}
+
+impl Display for Combo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (kind, joiner) = match self {
+            Combo::Chord(_) => ("chord", "+"),
+            Combo::Sequence(_) => ("sequence", ">"),
+        };
+        let keys: Vec<String> = self.keys().iter().map(|xs| xs.to_string()).collect();
+        write!(f, "{kind} {}", keys.join(joiner))
+    }
+}
+
+/// One chord or sequence bound to an action, parsed from the settings file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyBinding {
+    /// The binding's name; its key in the settings file.
+    pub name: String,
+    pub action: Action,
+    pub combo: Combo,
+}
+
+impl KeyBinding {
+    /// Parse a binding from its action name and settings-file value.
+    pub fn parse(name: &str, value: &str) -> Result<Self> {
+        let action = match name.trim().to_lowercase().as_str() {
+            "power" => Action::Power,
+            "utility" => Action::Utility,
+            "left" => Action::Left,
+            "right" => Action::Right,
+            "activate" => Action::Activate,
+            "equipment" => Action::Equipment,
+            "unequiphands" => Action::UnequipHands,
+            "showhide" => Action::ShowHide,
+            "refreshlayout" => Action::RefreshLayout,
+            "undo" => Action::Undo,
+            "redo" => Action::Redo,
+            "equipsetswap" => Action::EquipSetSwap,
+            _ => return Err(eyre!("unknown action '{name}'")),
+        };
+        Ok(Self {
+            name: name.trim().to_string(),
+            action,
+            combo: Combo::parse(value)?,
+        })
+    }
+}
+
+/// Parse the chord section of the settings file, logging anything that
+/// doesn't parse. Bindings that get in each other's way are reported with
+/// the other hotkey conflicts.
+pub fn bindings_from_settings(pairs: &[(String, String)]) -> Vec<KeyBinding> {
+    pairs
+        .iter()
+        .filter_map(|(name, value)| match KeyBinding::parse(name, value) {
+            Ok(binding) => Some(binding),
+            Err(e) => {
+                log::warn!("Ignoring the key binding for '{name}'; {e:#}");
+                None
+            }
+        })
+        .collect()
+}
+
+/// What the chord watcher made of a key event.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ChordOutcome {
+    /// Nothing to do with chords; handle the key as usual.
+    None,
+    /// A chord or sequence just completed.
+    Fired(Action),
+    /// The key was part of a chord that already fired, so ignore it.
+    Swallowed,
+}
+
+/// Tracks held keys and recent taps to spot chords and sequences.
+#[derive(Debug, Clone, Default)]
+pub struct ChordWatch {
+    bindings: Vec<KeyBinding>,
+    /// Keys down right now, in the order they went down.
+    held: Vec<u32>,
+    /// Recent key taps, oldest first.
+    taps: Vec<(u32, Instant)>,
+    /// Keys from a chord that fired, ignored until they go up.
+    consumed: Vec<u32>,
+}
+
+impl ChordWatch {
+    pub fn set_bindings(&mut self, bindings: Vec<KeyBinding>) {
+        self.bindings = bindings;
+        self.reset();
+    }
+
+    /// Forget every key we've seen.
+    pub fn reset(&mut self) {
+        self.held.clear();
+        self.taps.clear();
+        self.consumed.clear();
+    }
+
+    /// Keys from a chord that fired and are still down.
+    pub fn consumed(&self) -> &[u32] {
+        &self.consumed
+    }
+
+    /// Look at a key event. A chord fires when its last key goes down while
+    /// the rest of it are held; other held keys, like movement, don't matter.
+    /// A sequence fires when its last key comes up. `window` is the longest
+    /// gap allowed between taps in a sequence.
+    pub fn observe(
+        &mut self,
+        key: u32,
+        state: &KeyState,
+        now: Instant,
+        window: Duration,
+    ) -> ChordOutcome {
+        if self.bindings.is_empty() {
+            return ChordOutcome::None;
+        }
+
+        match state {
+            KeyState::Down => {
+                if !self.held.contains(&key) {
+                    self.held.push(key);
+                }
+                // The biggest chord wins when one holds another.
+                let Some(binding) = self
+                    .bindings
+                    .iter()
+                    .filter(|binding| match &binding.combo {
+                        Combo::Chord(keys) => {
+                            keys.contains(&key) && keys.iter().all(|xs| self.held.contains(xs))
+                        }
+                        Combo::Sequence(_) => false,
+                    })
+                    .max_by_key(|binding| binding.combo.keys().len())
+                else {
+                    return ChordOutcome::None;
+                };
+                for xs in binding.combo.keys() {
+                    if !self.consumed.contains(xs) {
+                        self.consumed.push(*xs);
+                    }
+                }
+                self.taps.clear();
+                ChordOutcome::Fired(binding.action)
+            }
+            KeyState::Pressed => {
+                if self.consumed.contains(&key) {
+                    ChordOutcome::Swallowed
+                } else {
+                    ChordOutcome::None
+                }
+            }
+            KeyState::Up => {
+                self.held.retain(|xs| *xs != key);
+                if self.consumed.contains(&key) {
+                    self.consumed.retain(|xs| *xs != key);
+                    return ChordOutcome::Swallowed;
+                }
+
+                if let Some((_, last)) = self.taps.last() {
+                    if now.duration_since(*last) > window {
+                        self.taps.clear();
+                    }
+                }
+                self.taps.push((key, now));
+                if self.taps.len() > MAX_COMBO_KEYS {
+                    self.taps.remove(0);
+                }
+
+                // Longer sequences win, so one that ends another still gets a turn.
+                let tapped: Vec<u32> = self.taps.iter().map(|(xs, _)| *xs).collect();
+                let mut matches: Vec<&KeyBinding> = self
+                    .bindings
+                    .iter()
+                    .filter(|binding| match &binding.combo {
+                        Combo::Sequence(keys) => tapped.ends_with(keys),
+                        Combo::Chord(_) => false,
+                    })
+                    .collect();
+                matches.sort_by_key(|binding| std::cmp::Reverse(binding.combo.keys().len()));
+                let Some(binding) = matches.first() else {
+                    return ChordOutcome::None;
+                };
+                self.taps.clear();
+                ChordOutcome::Fired(binding.action)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bindings(pairs: &[(&str, &str)]) -> Vec<KeyBinding> {
+        pairs
+            .iter()
+            .map(|(name, value)| KeyBinding::parse(name, value).expect("this binding should parse"))
+            .collect()
+    }
+
+    #[test]
+    fn parsing_bindings() {
+        let binding = KeyBinding::parse("Undo", "44 + 29").expect("this chord should parse");
+        assert_eq!(binding.action, Action::Undo);
+        assert_eq!(binding.combo, Combo::Chord(vec![29, 44]));
+        assert_eq!(binding.combo.to_string(), "chord 29+44");
+
+        let binding = KeyBinding::parse("showhide", "56>56").expect("this sequence should parse");
+        assert_eq!(binding.action, Action::ShowHide);
+        assert_eq!(binding.combo, Combo::Sequence(vec![56, 56]));
+
+        assert!(KeyBinding::parse("Dance", "29+44").is_err());
+        assert!(KeyBinding::parse("Undo", "29").is_err());
+        assert!(KeyBinding::parse("Undo", "29+29").is_err());
+        assert!(KeyBinding::parse("Undo", "29+44>45").is_err());
+        assert!(KeyBinding::parse("Undo", "1+2+3+4").is_err());
+        assert!(KeyBinding::parse("Undo", "ctrl+z").is_err());
+    }
+
+    #[test]
+    fn chords_fire_and_swallow_their_keys() {
+        let mut watch = ChordWatch::default();
+        watch.set_bindings(bindings(&[("Undo", "29+44")]));
+        let now = Instant::now();
+        let window = Duration::from_millis(500);
+
+        assert_eq!(
+            watch.observe(29, &KeyState::Down, now, window),
+            ChordOutcome::None
+        );
+        assert_eq!(
+            watch.observe(44, &KeyState::Down, now, window),
+            ChordOutcome::Fired(Action::Undo)
+        );
+        assert_eq!(
+            watch.observe(44, &KeyState::Pressed, now, window),
+            ChordOutcome::Swallowed
+        );
+        assert_eq!(
+            watch.observe(44, &KeyState::Up, now, window),
+            ChordOutcome::Swallowed
+        );
+        assert_eq!(
+            watch.observe(29, &KeyState::Up, now, window),
+            ChordOutcome::Swallowed
+        );
+        // A lone key afterwards is left alone.
+        watch.observe(44, &KeyState::Down, now, window);
+        assert_eq!(
+            watch.observe(44, &KeyState::Up, now, window),
+            ChordOutcome::None
+        );
+    }
+
+    #[test]
+    fn chords_fire_while_other_keys_are_held() {
+        let mut watch = ChordWatch::default();
+        watch.set_bindings(bindings(&[("Undo", "29+44")]));
+        let now = Instant::now();
+        let window = Duration::from_millis(500);
+
+        // Walking forward with W the whole time.
+        watch.observe(17, &KeyState::Down, now, window);
+        watch.observe(29, &KeyState::Down, now, window);
+        assert_eq!(
+            watch.observe(44, &KeyState::Down, now, window),
+            ChordOutcome::Fired(Action::Undo)
+        );
+        assert_eq!(watch.consumed(), &[29, 44]);
+        // W isn't part of the chord, so it goes back to the game.
+        assert_eq!(
+            watch.observe(17, &KeyState::Pressed, now, window),
+            ChordOutcome::None
+        );
+        assert_eq!(
+            watch.observe(29, &KeyState::Up, now, window),
+            ChordOutcome::Swallowed
+        );
+        assert_eq!(
+            watch.observe(44, &KeyState::Up, now, window),
+            ChordOutcome::Swallowed
+        );
+
+        // Pressing an unrelated key while the chord is held doesn't fire it again.
+        watch.observe(29, &KeyState::Down, now, window);
+        watch.observe(44, &KeyState::Down, now, window);
+        assert_eq!(
+            watch.observe(30, &KeyState::Down, now, window),
+            ChordOutcome::None
+        );
+    }
+
+    #[test]
+    fn sequences_fire_within_the_window() {
+        let mut watch = ChordWatch::default();
+        watch.set_bindings(bindings(&[("ShowHide", "56>57"), ("Redo", "55>56>57")]));
+        let start = Instant::now();
+        let window = Duration::from_millis(500);
+        let tap = |watch: &mut ChordWatch, key: u32, ms: u64| {
+            let at = start + Duration::from_millis(ms);
+            watch.observe(key, &KeyState::Down, at, window);
+            watch.observe(key, &KeyState::Up, at, window)
+        };
+
+        assert_eq!(tap(&mut watch, 56, 0), ChordOutcome::None);
+        assert_eq!(
+            tap(&mut watch, 57, 300),
+            ChordOutcome::Fired(Action::ShowHide)
+        );
+
+        // Too slow.
+        assert_eq!(tap(&mut watch, 56, 1000), ChordOutcome::None);
+        assert_eq!(tap(&mut watch, 57, 1600), ChordOutcome::None);
+
+        // The longer sequence wins when both match.
+        tap(&mut watch, 55, 3000);
+        tap(&mut watch, 56, 3200);
+        assert_eq!(tap(&mut watch, 57, 3400), ChordOutcome::Fired(Action::Redo));
+    }
+}
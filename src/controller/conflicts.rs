@@ -0,0 +1,440 @@
+//! Hotkey conflict analysis.
+//!
+//! `Hotkey::from()` decides what a key does by walking an if-chain, so when
+//! two settings share a key, one of them quietly never fires. This module
+//! looks at every binding together and explains each clash: hotkeys sharing a
+//! key, hotkeys sitting on a modifier key, modifiers whose jobs collide when
+//! they share a key, long presses asked to do more than one thing, and chords
+//! or sequences that get in the way of each other or of the other bindings.
+
+use std::collections::HashMap;
+use std::fmt::Display;
+
+use super::chords::{Combo, KeyBinding};
+use super::keys::Modifier;
+
+/// The settings that bind keys, gathered up for analysis.
+#[derive(Debug, Clone, Default)]
+pub struct KeyMap {
+    /// Hotkeys by setting name, in the order `Hotkey::from()` checks them.
+    /// The first one bound to a key is the one that works.
+    pub hotkeys: Vec<(String, u32)>,
+    /// Modifiers that are set, with their setting names.
+    pub modifiers: Vec<(Modifier, String, u32)>,
+    /// For each hotkey without an explicit long-press binding, the long-press
+    /// behaviors turned on for it, in the order `long_press_action()` picks.
+    pub long_presses: Vec<(String, Vec<String>)>,
+    /// Chords and sequences from the `[Chords]` section that parsed.
+    pub chords: Vec<KeyBinding>,
+}
+
+/// One clash between bindings.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HotkeyConflict {
+    /// Several hotkeys on one key. Only the first does anything.
+    Overlap {
+        code: u32,
+        winner: String,
+        losers: Vec<String>,
+    },
+    /// A hotkey on a modifier's key. Modifiers are checked first, so the
+    /// hotkey never fires.
+    ModifierClash {
+        code: u32,
+        modifier: String,
+        hotkey: String,
+    },
+    /// Modifiers sharing a key when their jobs overlap.
+    UnsupportedOverload {
+        code: u32,
+        first: String,
+        second: String,
+        why: &'static str,
+    },
+    /// A long press turned on for more than one job.
+    LongPressOverload {
+        hotkey: String,
+        winner: String,
+        losers: Vec<String>,
+    },
+    /// Two chord or sequence bindings with the same keys.
+    ComboOverlap {
+        combo: String,
+        first: String,
+        second: String,
+    },
+    /// A chord whose keys are all in a bigger chord, so it fires first.
+    ComboShadowed {
+        smaller: String,
+        smaller_combo: String,
+        larger: String,
+        larger_combo: String,
+    },
+    /// A chord or sequence using a key that's also a hotkey or modifier.
+    ComboSharesKey {
+        code: u32,
+        binding: String,
+        combo: String,
+        other: String,
+    },
+}
+
+impl Display for HotkeyConflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HotkeyConflict::Overlap {
+                code,
+                winner,
+                losers,
+            } => write!(
+                f,
+                "Key {code} is bound to {winner} and {}. Only {winner} works, because it is checked first.",
+                losers.join(" and ")
+            ),
+            HotkeyConflict::ModifierClash {
+                code,
+                modifier,
+                hotkey,
+            } => write!(
+                f,
+                "Key {code} is both the modifier {modifier} and the hotkey {hotkey}. Modifiers are checked first, so {hotkey} never fires."
+            ),
+            HotkeyConflict::UnsupportedOverload {
+                code,
+                first,
+                second,
+                why,
+            } => write!(
+                f,
+                "Key {code} is both {first} and {second}, which can't share a key: {why}."
+            ),
+            HotkeyConflict::LongPressOverload {
+                hotkey,
+                winner,
+                losers,
+            } => write!(
+                f,
+                "A long press of {hotkey} is set to {winner} and also to {}. Only {winner} happens.",
+                losers.join(" and ")
+            ),
+            HotkeyConflict::ComboOverlap {
+                combo,
+                first,
+                second,
+            } => write!(
+                f,
+                "The {combo} is bound to both {first} and {second}. Only one of them can fire."
+            ),
+            HotkeyConflict::ComboShadowed {
+                smaller,
+                smaller_combo,
+                larger,
+                larger_combo,
+            } => write!(
+                f,
+                "{smaller} fires before {larger} can, because its {smaller_combo} is part of the {larger_combo}."
+            ),
+            HotkeyConflict::ComboSharesKey {
+                code,
+                binding,
+                combo,
+                other,
+            } => write!(
+                f,
+                "Key {code} is in the {combo} for {binding} and is also {other}. {other} still acts on that key, so they can get in each other's way."
+            ),
+        }
+    }
+}
+
+/// Why two modifiers can't share a key, or None if they can.
+fn overload_problem(first: Modifier, second: Modifier) -> Option<&'static str> {
+    use Modifier::*;
+    match (first, second) {
+        (Cycle, Unequip) | (Unequip, Cycle) => {
+            Some("the power and hand keys can't tell cycling from unequipping")
+        }
+        (Cycle, Activate) | (Activate, Cycle) => {
+            Some("the utility key can't tell cycling from using the item")
+        }
+        (Cycle, Reverse) | (Reverse, Cycle) => Some("every cycle would go backward"),
+        _ => None,
+    }
+}
+
+/// Find every conflict in this set of bindings.
+pub fn find_conflicts(map: &KeyMap) -> Vec<HotkeyConflict> {
+    let mut conflicts = Vec::new();
+
+    // Hotkeys sharing a key, reported in key order so the log is stable.
+    let mut by_code: HashMap<u32, Vec<&String>> = HashMap::new();
+    let mut codes: Vec<u32> = Vec::new();
+    for (name, code) in map.hotkeys.iter() {
+        let names = by_code.entry(*code).or_default();
+        if names.is_empty() {
+            codes.push(*code);
+        }
+        names.push(name);
+    }
+    codes.sort();
+
+    for code in codes.iter() {
+        let names = &by_code[code];
+        if let Some((_, name, _)) = map.modifiers.iter().find(|(_, _, m)| m == code) {
+            for hotkey in names.iter() {
+                conflicts.push(HotkeyConflict::ModifierClash {
+                    code: *code,
+                    modifier: name.clone(),
+                    hotkey: hotkey.to_string(),
+                });
+            }
+        } else if names.len() > 1 {
+            conflicts.push(HotkeyConflict::Overlap {
+                code: *code,
+                winner: names[0].clone(),
+                losers: names[1..].iter().map(|xs| xs.to_string()).collect(),
+            });
+        }
+    }
+
+    for (i, (first, first_name, first_code)) in map.modifiers.iter().enumerate() {
+        for (second, second_name, second_code) in map.modifiers.iter().skip(i + 1) {
+            if first_code != second_code {
+                continue;
+            }
+            if let Some(why) = overload_problem(*first, *second) {
+                conflicts.push(HotkeyConflict::UnsupportedOverload {
+                    code: *first_code,
+                    first: first_name.clone(),
+                    second: second_name.clone(),
+                    why,
+                });
+            }
+        }
+    }
+
+    for (hotkey, uses) in map.long_presses.iter() {
+        if uses.len() > 1 {
+            conflicts.push(HotkeyConflict::LongPressOverload {
+                hotkey: hotkey.clone(),
+                winner: uses[0].clone(),
+                losers: uses[1..].to_vec(),
+            });
+        }
+    }
+
+    conflicts.extend(combo_conflicts(map));
+    conflicts
+}
+
+/// Chords and sequences that clash with each other or with a single-key binding.
+fn combo_conflicts(map: &KeyMap) -> Vec<HotkeyConflict> {
+    let mut conflicts = Vec::new();
+    for (i, first) in map.chords.iter().enumerate() {
+        for second in map.chords.iter().skip(i + 1) {
+            if first.combo == second.combo {
+                conflicts.push(HotkeyConflict::ComboOverlap {
+                    combo: first.combo.to_string(),
+                    first: first.name.clone(),
+                    second: second.name.clone(),
+                });
+                continue;
+            }
+            if let (Combo::Chord(a), Combo::Chord(b)) = (&first.combo, &second.combo) {
+                let (smaller, larger) = if a.len() < b.len() {
+                    (first, second)
+                } else {
+                    (second, first)
+                };
+                let inside = smaller
+                    .combo
+                    .keys()
+                    .iter()
+                    .all(|xs| larger.combo.keys().contains(xs));
+                if a.len() != b.len() && inside {
+                    conflicts.push(HotkeyConflict::ComboShadowed {
+                        smaller: smaller.name.clone(),
+                        smaller_combo: smaller.combo.to_string(),
+                        larger: larger.name.clone(),
+                        larger_combo: larger.combo.to_string(),
+                    });
+                }
+            }
+        }
+    }
+
+    for binding in map.chords.iter() {
+        let mut seen: Vec<u32> = Vec::new();
+        for code in binding.combo.keys() {
+            if seen.contains(code) {
+                continue;
+            }
+            seen.push(*code);
+            let modifiers = map
+                .modifiers
+                .iter()
+                .filter(|(_, _, m)| m == code)
+                .map(|(_, name, _)| name);
+            let hotkeys = map
+                .hotkeys
+                .iter()
+                .filter(|(_, h)| h == code)
+                .map(|(name, _)| name);
+            for other in modifiers.chain(hotkeys) {
+                conflicts.push(HotkeyConflict::ComboSharesKey {
+                    code: *code,
+                    binding: binding.name.clone(),
+                    combo: binding.combo.to_string(),
+                    other: other.clone(),
+                });
+            }
+        }
+    }
+    conflicts
+}
+
+/// Log every conflict.
+pub fn log_conflicts(conflicts: &[HotkeyConflict]) {
+    if conflicts.is_empty() {
+        log::debug!("No hotkey conflicts found.");
+        return;
+    }
+    log::warn!("Found {} hotkey conflicts:", conflicts.len());
+    for conflict in conflicts.iter() {
+        log::warn!("    {conflict}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn named(name: &str, code: u32) -> (String, u32) {
+        (name.to_string(), code)
+    }
+
+    #[test]
+    fn no_conflicts_in_a_clean_map() {
+        let map = KeyMap {
+            hotkeys: vec![named("uPowerCycleKey", 3), named("uRefreshKey", 8)],
+            modifiers: vec![
+                (Modifier::Cycle, "iCycleModifierKey".to_string(), 42),
+                (Modifier::Menu, "iMenuModifierKey".to_string(), 42),
+            ],
+            long_presses: vec![("uPowerCycleKey".to_string(), vec!["unequip".to_string()])],
+            chords: vec![KeyBinding::parse("Undo", "29+44").expect("this chord should parse")],
+        };
+        assert!(find_conflicts(&map).is_empty());
+    }
+
+    #[test]
+    fn finding_each_kind_of_conflict() {
+        let map = KeyMap {
+            hotkeys: vec![
+                named("uPowerCycleKey", 3),
+                named("uLeftCycleKey", 5),
+                named("uRefreshKey", 3),
+                named("iUndoKey", 42),
+            ],
+            modifiers: vec![
+                (Modifier::Cycle, "iCycleModifierKey".to_string(), 42),
+                (Modifier::Reverse, "iReverseModifierKey".to_string(), 42),
+            ],
+            long_presses: vec![(
+                "uLeftCycleKey".to_string(),
+                vec!["unequip".to_string(), "advance".to_string()],
+            )],
+            chords: Vec::new(),
+        };
+        let conflicts = find_conflicts(&map);
+        assert_eq!(
+            conflicts,
+            vec![
+                HotkeyConflict::Overlap {
+                    code: 3,
+                    winner: "uPowerCycleKey".to_string(),
+                    losers: vec!["uRefreshKey".to_string()],
+                },
+                HotkeyConflict::ModifierClash {
+                    code: 42,
+                    modifier: "iCycleModifierKey".to_string(),
+                    hotkey: "iUndoKey".to_string(),
+                },
+                HotkeyConflict::UnsupportedOverload {
+                    code: 42,
+                    first: "iCycleModifierKey".to_string(),
+                    second: "iReverseModifierKey".to_string(),
+                    why: "every cycle would go backward",
+                },
+                HotkeyConflict::LongPressOverload {
+                    hotkey: "uLeftCycleKey".to_string(),
+                    winner: "unequip".to_string(),
+                    losers: vec!["advance".to_string()],
+                },
+            ]
+        );
+        assert_eq!(
+            conflicts[0].to_string(),
+            "Key 3 is bound to uPowerCycleKey and uRefreshKey. Only uPowerCycleKey works, because it is checked first."
+        );
+    }
+
+    #[test]
+    fn finding_chord_conflicts() {
+        let chords = [
+            ("Undo", "29+44"),
+            ("Redo", "44+29"),
+            ("EquipSetSwap", "29+44+42"),
+            ("ShowHide", "29>44"),
+            ("RefreshLayout", "3+56"),
+        ]
+        .iter()
+        .map(|(name, value)| KeyBinding::parse(name, value).expect("this binding should parse"))
+        .collect();
+        let map = KeyMap {
+            hotkeys: vec![named("uPowerCycleKey", 3)],
+            modifiers: vec![(Modifier::Cycle, "iCycleModifierKey".to_string(), 56)],
+            long_presses: Vec::new(),
+            chords,
+        };
+        let conflicts = find_conflicts(&map);
+        assert_eq!(
+            conflicts,
+            vec![
+                HotkeyConflict::ComboOverlap {
+                    combo: "chord 29+44".to_string(),
+                    first: "Undo".to_string(),
+                    second: "Redo".to_string(),
+                },
+                HotkeyConflict::ComboShadowed {
+                    smaller: "Undo".to_string(),
+                    smaller_combo: "chord 29+44".to_string(),
+                    larger: "EquipSetSwap".to_string(),
+                    larger_combo: "chord 29+42+44".to_string(),
+                },
+                HotkeyConflict::ComboShadowed {
+                    smaller: "Redo".to_string(),
+                    smaller_combo: "chord 29+44".to_string(),
+                    larger: "EquipSetSwap".to_string(),
+                    larger_combo: "chord 29+42+44".to_string(),
+                },
+                HotkeyConflict::ComboSharesKey {
+                    code: 3,
+                    binding: "RefreshLayout".to_string(),
+                    combo: "chord 3+56".to_string(),
+                    other: "uPowerCycleKey".to_string(),
+                },
+                HotkeyConflict::ComboSharesKey {
+                    code: 56,
+                    binding: "RefreshLayout".to_string(),
+                    combo: "chord 3+56".to_string(),
+                    other: "iCycleModifierKey".to_string(),
+                },
+            ]
+        );
+        assert_eq!(
+            conflicts[1].to_string(),
+            "Undo fires before EquipSetSwap can, because its chord 29+44 is part of the chord 29+42+44."
+        );
+    }
+}
@@ -13,17 +13,27 @@
 //! set is itself complex.
 
 use std::collections::HashMap;
+use std::str::FromStr;
 use std::sync::Mutex;
+use std::time::{Duration, Instant};
 
 use cxx::let_cxx_string;
 use once_cell::sync::Lazy;
 use strfmt::strfmt;
 
+use super::autofill::{AutoFillRule, AutoFillSort};
+use super::chords::{self, ChordOutcome, ChordWatch};
 use super::cycles::*;
+use super::filters::AdvanceFilter;
+use super::history::History;
 use super::keys::*;
+use super::loadout::{self, Loadout};
+use super::macros::{MacroState, MacroStep};
+use super::quickslots::QuickslotTarget;
 use super::settings::{settings, ActivationMethod, UnarmedMethod};
+use super::triggers::{GameState, Trigger, TriggerWatch};
 use crate::cycleentries::*;
-use crate::data::item_cache::ItemCache;
+use crate::data::item_cache::{fetch_game_item, ItemCache};
 use crate::data::potion::PotionType;
 use crate::data::*;
 use crate::layouts::Layout;
@@ -55,8 +65,22 @@ pub struct Controller {
     right_hand_cached: String,
     /// We need to track keystate to implement modifier keys.
     tracked_keys: HashMap<u32, TrackedKey>,
+    /// Watches for key chords and sequences from the settings file.
+    chords: ChordWatch,
     /// True if we're using CGO's alternative grip.
     cgo_alt_grip: bool,
+    /// When each cycle was last advanced, so pinned entries can reclaim the top spot.
+    last_advanced: HashMap<CycleSlot, Instant>,
+    /// Cycle and equipment set edits the player can undo.
+    history: History<CycleSnapshot>,
+    /// What we saw last time we checked equipset triggers.
+    trigger_watch: TriggerWatch,
+    /// The id of the equipment set we equipped most recently.
+    current_equipset: Option<u32>,
+    /// The id of the set equipped before that one, so the player can swap back.
+    previous_equipset: Option<u32>,
+    /// The macro being recorded or played, if any.
+    macros: MacroState,
 }
 
 impl Controller {
@@ -70,20 +94,414 @@ impl Controller {
             left_hand_cached: "".to_string(),
             right_hand_cached: "".to_string(),
             tracked_keys: HashMap::new(),
+            chords: ChordWatch::default(),
             cgo_alt_grip: false,
+            last_advanced: HashMap::new(),
+            history: History::default(),
+            trigger_watch: TriggerWatch::default(),
+            current_equipset: None,
+            previous_equipset: None,
+            macros: MacroState::default(),
         }
     }
 
     /// Called after a save load to initialize state. The validate function logs out cycles.
     pub fn refresh_after_load(&mut self) {
+        self.history.clear();
+        self.trigger_watch.reset();
+        self.current_equipset = None;
+        self.previous_equipset = None;
+        self.stop_macro();
+        self.macros.finish_recording();
         self.cycles.validate(&mut self.cache);
         self.update_hud();
     }
 
+    /// Give a character with no saved cycles a starting point. The template is
+    /// chosen by the tag in the settings file or by the player's race, and then
+    /// validated so anything this character doesn't have is dropped. Only the
+    /// first call for a character does anything, so reopening the race menu
+    /// later doesn't throw away the player's cycles.
+    pub fn apply_template(&mut self) {
+        self.cycles.loaded = true;
+        let tag = settings().template_tag();
+        let race = playerRaceName();
+        let Some((name, mut template)) = loadout::find_template(&tag, &race) else {
+            log::info!("No cycle template found for tag='{tag}' or race='{race}'; starting empty.");
+            return;
+        };
+
+        let missing = template.retain_found(|spec| {
+            !matches!(self.cache.get(&spec.to_string()).kind(), BaseType::Empty)
+        });
+        if !missing.is_empty() {
+            log::info!(
+                "Template '{name}' names {} items the game can't find; they were left out.",
+                missing.len()
+            );
+        }
+        self.cycles.apply_loadout(template, true, &mut self.cache);
+        self.cycles.validate(&mut self.cache);
+        self.cycles.resort(&mut self.cache);
+        log::info!("Started this character with the '{name}' cycle template.");
+        self.update_hud();
+    }
+
     /// Called by the MCM cycle clear button.
     pub fn clear_cycles(&mut self) {
         log::info!("Clearing all cycles. Turning off targeting computer.");
+        let before = self.cycles.snapshot();
         self.cycles.clear();
+        self.remember_edit("all cycles cleared".to_string(), before);
+    }
+
+    /// Make a new equipment set from what the player is wearing and has readied,
+    /// including their power or shout and ammo. Papyrus support.
+    pub fn create_equipset(&mut self, name: String, data: EquippedData) -> bool {
+        let before = self.cycles.snapshot();
+        let label = format!("equipment set '{name}' created");
+        let created = self.cycles.add_equipset(name.clone(), data);
+        if created {
+            let id = self.cycles.equipset_by_name(name);
+            for part in [SetPart::Power, SetPart::Ammo, SetPart::Left, SetPart::Right] {
+                self.cycles
+                    .set_equipset_part(id, part, Some(self.equipped_part(part)));
+            }
+        }
+        self.remember_edit(label, before);
+        created
+    }
+
+    /// Replace an equipment set's contents with what the player is wearing. The
+    /// power, ammo, and hand parts are refreshed only if the set already
+    /// changes them. A set based on another keeps only what differs from its
+    /// parent. Papyrus support.
+    pub fn update_equipset(&mut self, id: u32, data: EquippedData) -> bool {
+        let before = self.cycles.snapshot();
+        let parts = self
+            .cycles
+            .equipset_by_id(id)
+            .map(|xs| xs.parts())
+            .unwrap_or_default();
+        let updated = self.cycles.update_equipset(id, data);
+        for part in parts.into_iter().filter(|xs| *xs != SetPart::Armor) {
+            self.cycles
+                .set_equipset_part(id, part, Some(self.equipped_part(part)));
+        }
+        self.trim_to_parent(id);
+        self.remember_edit(format!("equipment set {id} updated"), before);
+        updated
+    }
+
+    /// Get the names of the parts of the player's gear an equipment set
+    /// changes: armor, power, ammo, left, and right. Papyrus support.
+    pub fn equipset_parts(&self, id: u32) -> Vec<String> {
+        self.cycles
+            .equipset_by_id(id)
+            .map(|set| set.parts().iter().map(|xs| xs.to_string()).collect())
+            .unwrap_or_default()
+    }
+
+    /// Turn one part of an equipment set on or off. Turning a part on records
+    /// what the player has in that part right now. Papyrus support.
+    pub fn set_equipset_part(&mut self, id: u32, part_name: &str, enabled: bool) -> bool {
+        let Ok(part) = SetPart::from_str(part_name) else {
+            log::warn!("'{part_name}' is not a part of an equipment set.");
+            return false;
+        };
+        let before = self.cycles.snapshot();
+        let changed = match (part, enabled) {
+            (SetPart::Armor, true) => {
+                let data = getEquippedItems();
+                self.cycles.update_equipset(id, *data)
+            }
+            (SetPart::Armor, false) => self.cycles.update_equipset(
+                id,
+                EquippedData {
+                    items: Vec::new(),
+                    empty_slots: Vec::new(),
+                },
+            ),
+            (_, true) => {
+                let spec = self.equipped_part(part);
+                self.cycles.set_equipset_part(id, part, Some(spec))
+            }
+            (_, false) => self.cycles.set_equipset_part(id, part, None),
+        };
+        let label = if enabled {
+            format!("{part} added to equipment set {id}")
+        } else {
+            format!("{part} removed from equipment set {id}")
+        };
+        self.remember_edit(label, before);
+        changed
+    }
+
+    /// Get an equipment set's triggers by name. Papyrus support.
+    pub fn equipset_triggers(&self, id: u32) -> Vec<String> {
+        self.cycles
+            .equipset_by_id(id)
+            .map(|set| set.triggers.iter().map(|xs| xs.to_string()).collect())
+            .unwrap_or_default()
+    }
+
+    /// Replace an equipment set's triggers with a list like `combat, werewolf`.
+    /// Nothing changes if any trigger in the list is unknown. Papyrus support.
+    pub fn set_equipset_triggers(&mut self, id: u32, triggers: &str) -> bool {
+        let parsed = match Trigger::parse_list(triggers) {
+            Ok(v) => v,
+            Err(e) => {
+                log::warn!("Can't set triggers for equipment set {id}; {e:#}");
+                return false;
+            }
+        };
+        let before = self.cycles.snapshot();
+        let changed = self.cycles.set_equipset_triggers(id, parsed);
+        self.remember_edit(format!("triggers for equipment set {id} changed"), before);
+        changed
+    }
+
+    /// Are an equipment set's triggers switched on? Papyrus support.
+    pub fn equipset_auto_equip(&self, id: u32) -> bool {
+        self.cycles
+            .equipset_by_id(id)
+            .is_some_and(|set| set.auto_equip)
+    }
+
+    /// Switch an equipment set's triggers on or off. Papyrus support.
+    pub fn set_equipset_auto_equip(&mut self, id: u32, enabled: bool) -> bool {
+        let before = self.cycles.snapshot();
+        let changed = self.cycles.enable_equipset_triggers(id, enabled);
+        let label = if enabled {
+            format!("automatic equipping turned on for equipment set {id}")
+        } else {
+            format!("automatic equipping turned off for equipment set {id}")
+        };
+        self.remember_edit(label, before);
+        changed
+    }
+
+    /// Look at the game state and equip a set if one of its triggers has just
+    /// started to hold. We only ask the game anything if some set has triggers
+    /// switched on.
+    pub fn check_equipset_triggers(&mut self) {
+        let candidates = self.cycles.triggered_equipsets();
+        if candidates.is_empty() {
+            return;
+        }
+        let state = GameState {
+            in_combat: isInCombat(),
+            weapons_drawn: weaponsAreDrawn(),
+            werewolf: isWerewolf(),
+            vampire_lord: isVampireLord(),
+            location_keywords: currentLocationKeywords(),
+        };
+        let cooldown = Duration::from_millis(settings().trigger_cooldown_ms() as u64);
+        let Some(id) = self
+            .trigger_watch
+            .check(state, &candidates, cooldown, Instant::now())
+        else {
+            return;
+        };
+        if let Some(set) = self.cycles.select_equipset_id(id) {
+            log::info!(
+                "A trigger fired for equipment set '{}'; equipping it.",
+                set.name()
+            );
+            self.equip_selected_set();
+        }
+    }
+
+    /// The form spec for what the player has in a hand, power, or ammo slot.
+    /// Empty if the slot is empty.
+    fn equipped_part(&self, part: SetPart) -> String {
+        match part {
+            SetPart::Armor => String::new(),
+            SetPart::Power => specEquippedPower(),
+            SetPart::Ammo => specEquippedAmmo(),
+            SetPart::Left => specEquippedLeft(),
+            SetPart::Right => specEquippedRight(),
+        }
+    }
+
+    /// Rename an equipment set. Papyrus support.
+    pub fn rename_equipset(&mut self, id: u32, name: String) -> bool {
+        let before = self.cycles.snapshot();
+        let label = format!("equipment set {id} renamed to '{name}'");
+        let renamed = self.cycles.rename_equipset(id, name);
+        self.remember_edit(label, before);
+        renamed
+    }
+
+    /// Remove an equipment set. Sets based on it are filled in with what they
+    /// inherited so they keep working on their own. Papyrus support.
+    pub fn remove_equipset(&mut self, id: u32) -> bool {
+        let before = self.cycles.snapshot();
+        for child in self.cycles.equipset_children(id) {
+            self.flatten_equipset(child);
+        }
+        let removed = self.cycles.remove_equipset(id.to_string());
+        self.remember_edit(format!("equipment set {id} removed"), before);
+        removed
+    }
+
+    /// Get the id of the set this equipment set is based on, or -1 if it
+    /// stands alone. Papyrus support.
+    pub fn equipset_parent(&self, id: u32) -> i32 {
+        self.cycles
+            .equipset_by_id(id)
+            .and_then(|xs| xs.parent)
+            .map_or(-1, |xs| xs as i32)
+    }
+
+    /// Base an equipment set on another, keeping only what differs from it, or
+    /// pass -1 to make the set stand alone with everything it inherited.
+    /// Papyrus support.
+    pub fn set_equipset_parent(&mut self, id: u32, parent: i32) -> bool {
+        let before = self.cycles.snapshot();
+        let changed = if parent < 0 {
+            self.flatten_equipset(id)
+        } else if self.cycles.set_equipset_parent(id, Some(parent as u32)) {
+            self.trim_to_parent(id);
+            true
+        } else {
+            false
+        };
+        let label = if parent < 0 {
+            format!("equipment set {id} made standalone")
+        } else {
+            format!("equipment set {id} based on set {parent}")
+        };
+        self.remember_edit(label, before);
+        changed
+    }
+
+    /// Fill in everything a set inherits and cut it loose from its parent.
+    fn flatten_equipset(&mut self, id: u32) -> bool {
+        let Some(mut resolved) = self.cycles.resolve_equipset(id, &armor_slot_mask) else {
+            return false;
+        };
+        resolved.parent = None;
+        self.cycles.replace_equipset(resolved)
+    }
+
+    /// Drop whatever a set has in common with its resolved parent.
+    fn trim_to_parent(&mut self, id: u32) {
+        let Some(mut set) = self.cycles.equipset_by_id(id) else {
+            return;
+        };
+        let Some(parent) = set
+            .parent
+            .and_then(|parent| self.cycles.resolve_equipset(parent, &armor_slot_mask))
+        else {
+            return;
+        };
+        set.trim_to_overrides(&parent);
+        self.cycles.replace_equipset(set);
+    }
+
+    /// Get the names of the loadout files the player can import. Papyrus support.
+    pub fn loadout_names(&self) -> Vec<String> {
+        loadout::loadout_names()
+    }
+
+    /// Write the cycles and equipment sets to the named loadout file. Papyrus support.
+    pub fn export_loadout(&self, name: &str) -> bool {
+        let result = loadout::loadout_path(name)
+            .and_then(|path| self.cycles.to_loadout().write_to_file(&path));
+        if let Err(e) = result {
+            log::warn!("Unable to export loadout '{name}'; {e:#}");
+            return false;
+        }
+        log::info!("Exported cycles and equipment sets to loadout '{name}'.");
+        let mut vars = HashMap::new();
+        vars.insert("name".to_string(), name.to_string());
+        if let Ok(message) = strfmt(&translated_key(FMT_LOADOUT_EXPORTED), &vars) {
+            notify(&message);
+        }
+        true
+    }
+
+    /// Read the named loadout file and merge it into the current cycles and
+    /// equipment sets, or replace them with it. Entries the game can't find
+    /// are reported and left out. Returns how many entries were missing, or
+    /// -1 if the file couldn't be read. Papyrus support.
+    pub fn import_loadout(&mut self, name: &str, replace: bool) -> i32 {
+        let read = loadout::loadout_path(name).and_then(|path| Loadout::read_from_file(&path));
+        let mut incoming = match read {
+            Ok(v) => v,
+            Err(e) => {
+                log::warn!("Unable to import loadout '{name}'; {e:#}");
+                return -1;
+            }
+        };
+
+        let missing = incoming.retain_found(|spec| {
+            !matches!(self.cache.get(&spec.to_string()).kind(), BaseType::Empty)
+        });
+        missing.iter().for_each(|spec| {
+            log::info!("Loadout '{name}' names an item the game can't find; spec={spec};");
+        });
+
+        let before = self.cycles.snapshot();
+        self.cycles
+            .apply_loadout(incoming, replace, &mut self.cache);
+        self.cycles.resort(&mut self.cache);
+        self.remember_edit(format!("loadout '{name}' imported"), before);
+
+        let mut vars = HashMap::new();
+        vars.insert("name".to_string(), name.to_string());
+        vars.insert("count".to_string(), missing.len().to_string());
+        if let Ok(message) = strfmt(&translated_key(FMT_LOADOUT_IMPORTED), &vars) {
+            notify(&message);
+        }
+        if !missing.is_empty() {
+            if let Ok(message) = strfmt(&translated_key(FMT_LOADOUT_MISSING), &vars) {
+                notify(&message);
+            }
+        }
+
+        self.update_hud();
+        missing.len() as i32
+    }
+
+    /// Undo the most recent cycle or equipment set edit.
+    pub fn undo_edit(&mut self) -> bool {
+        let Some((label, before)) = self.history.undo(self.cycles.snapshot()) else {
+            log::info!("There are no cycle edits to undo.");
+            return false;
+        };
+        self.cycles.restore(before);
+        self.after_history_step(FMT_UNDONE, label);
+        true
+    }
+
+    /// Redo the most recently undone edit.
+    pub fn redo_edit(&mut self) -> bool {
+        let Some((label, after)) = self.history.redo(self.cycles.snapshot()) else {
+            log::info!("There are no cycle edits to redo.");
+            return false;
+        };
+        self.cycles.restore(after);
+        self.after_history_step(FMT_REDONE, label);
+        true
+    }
+
+    /// Remember the cycles as they were before an edit, if the edit changed anything.
+    fn remember_edit(&mut self, label: String, before: CycleSnapshot) {
+        if self.cycles.snapshot() != before {
+            self.history.push(label, before);
+        }
+    }
+
+    fn after_history_step(&mut self, format: &str, label: String) {
+        let mut vars = HashMap::new();
+        vars.insert("change".to_string(), label);
+        if let Ok(message) = strfmt(&translated_key(format), &vars) {
+            log::info!("{message}");
+            notify(&message);
+        }
+        self.update_hud();
     }
 
     /// Get the names of all items in the given cycle. Papyrus support.
@@ -109,6 +527,220 @@ impl Controller {
         }
     }
 
+    /// Get the names of all saved cycle profiles. Papyrus support.
+    pub fn cycle_profile_names(&self) -> Vec<String> {
+        self.cycles.profile_names()
+    }
+
+    /// Save the current cycles as a new named profile. Papyrus support.
+    pub fn create_cycle_profile(&mut self, name: String) -> bool {
+        let before = self.cycles.snapshot();
+        let label = format!("cycle profile '{name}' created");
+        let created = self.cycles.create_profile(name);
+        self.remember_edit(label, before);
+        created
+    }
+
+    /// Swap in the cycles from the named profile, drop anything the player no
+    /// longer has, and show the results in the HUD.
+    pub fn switch_cycle_profile(&mut self, name: &str) -> bool {
+        let before = self.cycles.snapshot();
+        if !self.cycles.switch_profile(name) {
+            return false;
+        }
+        self.cycles.validate(&mut self.cache);
+        self.remember_edit(format!("switched to cycle profile '{name}'"), before);
+        self.update_hud();
+        true
+    }
+
+    /// Delete the named cycle profile. Papyrus support.
+    pub fn remove_cycle_profile(&mut self, name: &str) -> bool {
+        let before = self.cycles.snapshot();
+        let removed = self.cycles.remove_profile(name);
+        self.remember_edit(format!("cycle profile '{name}' removed"), before);
+        removed
+    }
+
+    /// Pin or unpin an entry in the given cycle. Papyrus support.
+    pub fn set_cycle_entry_pinned(&mut self, which: i32, form_spec: &str, pinned: bool) -> bool {
+        let Ok(slot) = CycleSlot::try_from(which) else {
+            return false;
+        };
+        self.cycles.set_pinned(&slot, form_spec, pinned)
+    }
+
+    /// Mark an entry in the given cycle as skipped when unusable. Papyrus support.
+    pub fn set_cycle_entry_skip_unusable(
+        &mut self,
+        which: i32,
+        form_spec: &str,
+        skip: bool,
+    ) -> bool {
+        let Ok(slot) = CycleSlot::try_from(which) else {
+            return false;
+        };
+        self.cycles.set_skip_unusable(&slot, form_spec, skip)
+    }
+
+    /// Get the form spec of the pinned entry in the given cycle; empty if none.
+    pub fn cycle_pinned(&self, which: i32) -> String {
+        let Ok(slot) = CycleSlot::try_from(which) else {
+            return String::new();
+        };
+        self.cycles.pinned(&slot).unwrap_or_default()
+    }
+
+    /// Get the form specs of entries skipped when unusable in the given cycle.
+    pub fn cycle_skip_unusable(&self, which: i32) -> Vec<String> {
+        let Ok(slot) = CycleSlot::try_from(which) else {
+            return Vec::new();
+        };
+        self.cycles.skip_unusable_entries(&slot)
+    }
+
+    /// Jump straight to the entry at the given index in a cycle, show it, and
+    /// equip it after the usual delay. Papyrus support.
+    pub fn select_cycle_index(&mut self, which: i32, index: usize) -> bool {
+        let Ok(slot) = CycleSlot::try_from(which) else {
+            return false;
+        };
+        let Some(form_spec) = self.cycles.select_index(&slot, index) else {
+            return false;
+        };
+        let item = self.cache.get_with_refresh(&form_spec);
+        self.update_slot(HudElement::from(&slot), &item);
+        if !matches!(slot, CycleSlot::Utility) {
+            startTimer(Action::from(slot), settings().equip_delay_ms());
+        }
+        true
+    }
+
+    /// Jump straight to the equipset at the given index and equip it after
+    /// the usual delay. Papyrus support.
+    pub fn select_equipset_index(&mut self, index: usize) -> bool {
+        if self.cycles.select_equipset_index(index).is_none() {
+            return false;
+        }
+        startTimer(Action::Equipment, settings().equip_delay_ms());
+        true
+    }
+
+    /// Sort a cycle by the named mode, once or kept applied. Passing `none`
+    /// with `continuous` set stops keeping the cycle sorted. Papyrus support.
+    pub fn sort_cycle(&mut self, which: i32, mode: &str, continuous: bool) -> bool {
+        let Ok(slot) = CycleSlot::try_from(which) else {
+            return false;
+        };
+        if continuous && mode.eq_ignore_ascii_case("none") {
+            let before = self.cycles.snapshot();
+            self.cycles.set_sort_mode(&slot, None, &mut self.cache);
+            self.remember_edit(format!("{slot} cycle no longer kept sorted"), before);
+            return true;
+        }
+        let Ok(mode) = SortMode::from_str(mode) else {
+            log::warn!("unknown cycle sort mode '{mode}'");
+            return false;
+        };
+        log::info!("sorting {slot} cycle by {mode}; continuous={continuous};");
+        let before = self.cycles.snapshot();
+        if continuous {
+            self.cycles
+                .set_sort_mode(&slot, Some(mode), &mut self.cache);
+        } else {
+            self.cycles.sort_cycle(&slot, mode, &mut self.cache);
+        }
+        self.remember_edit(format!("{slot} cycle sorted by {mode}"), before);
+        true
+    }
+
+    /// Get the name of the sort mode kept applied to a cycle; empty if none.
+    pub fn cycle_sort_mode(&self, which: i32) -> String {
+        let Ok(slot) = CycleSlot::try_from(which) else {
+            return String::new();
+        };
+        self.cycles
+            .sort_mode(&slot)
+            .map(|xs| xs.to_string())
+            .unwrap_or_default()
+    }
+
+    /// Get the names of the auto-fill rules from the settings file.
+    pub fn auto_fill_rule_names(&self) -> Vec<String> {
+        settings()
+            .auto_fill_rules()
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect()
+    }
+
+    /// Fill a cycle from the player's inventory, spells, and shouts using the
+    /// named auto-fill rule. Returns how many items were added, or -1 if the
+    /// rule is missing or can't be parsed.
+    pub fn auto_fill(&mut self, name: &str) -> i32 {
+        let Some((_, text)) = settings()
+            .auto_fill_rules()
+            .into_iter()
+            .find(|(rule_name, _)| rule_name == name)
+        else {
+            log::warn!("no auto-fill rule named '{name}'");
+            return -1;
+        };
+        let rule = match AutoFillRule::parse(name, &text) {
+            Ok(rule) => rule,
+            Err(e) => {
+                log::warn!("auto-fill rule '{name}' is invalid: {e:#}");
+                return -1;
+            }
+        };
+
+        // Fetch directly so that items we don't keep don't crowd the cache.
+        let candidates: Vec<(HudItem, f32)> = getInventoryFormSpecs()
+            .into_iter()
+            .map(|spec| {
+                let item = fetch_game_item(&spec);
+                let strength = if rule.sort == AutoFillSort::Strength && rule.matches(&item) {
+                    let_cxx_string!(form_spec = spec);
+                    formStrength(&form_spec)
+                } else {
+                    0.0
+                };
+                (item, strength)
+            })
+            .collect();
+        let chosen: Vec<HudItem> = rule
+            .select(candidates)
+            .into_iter()
+            .map(|spec| self.cache.get(&spec))
+            .collect();
+
+        let before = self.cycles.snapshot();
+        let added = self.cycles.fill(&rule.slot, &chosen, rule.replace);
+        self.cycles.resort(&mut self.cache);
+        self.remember_edit(format!("auto-fill rule '{name}' applied"), before);
+        log::info!(
+            "auto-fill rule '{name}' added {added} of {} matching items to the {} cycle",
+            chosen.len(),
+            rule.slot
+        );
+
+        let cyclename = match rule.slot {
+            CycleSlot::Power => translated_key(FMT_ITEM_POWERS_CYCLE),
+            CycleSlot::Utility => translated_key(FMT_ITEM_UTILITIES_CYCLE),
+            CycleSlot::Left => translated_key(FMT_ITEM_LEFT_CYCLE),
+            CycleSlot::Right => translated_key(FMT_ITEM_RIGHT_CYCLE),
+        };
+        let mut vars = HashMap::new();
+        vars.insert("count".to_string(), added.to_string());
+        vars.insert("cycle".to_string(), cyclename);
+        if let Ok(message) = strfmt(&translated_key(FMT_AUTO_FILLED), &vars) {
+            notify(&message);
+        }
+
+        self.update_hud();
+        added as i32
+    }
+
     /// Called after any settings file read to enforce them.
     pub fn apply_settings(&mut self) {
         let settings = settings();
@@ -176,6 +808,9 @@ impl Controller {
             }
         }
 
+        self.chords
+            .set_bindings(chords::bindings_from_settings(&settings.key_chords()));
+
         // Apply any new anchor relocations to the current layout.
         Layout::refresh();
 
@@ -314,13 +949,38 @@ impl Controller {
     /// Returns an enum indicating what we did in response, so that the C++ layer can
     /// start a tick timer for cycle delay.
     pub fn handle_key_event(&mut self, key: u32, button: &ButtonEvent) -> KeyEventResponse {
+        let window = Duration::from_millis(settings().key_sequence_ms() as u64);
+        let chord = self
+            .chords
+            .observe(key, &KeyState::from(button), Instant::now(), window);
+
         if matches!(Hotkey::from(key), Hotkey::None) {
-            return KeyEventResponse::default();
+            // Other keys belong to the game unless they finish a chord.
+            return match chord {
+                ChordOutcome::Fired(action) => self.handle_chord(action),
+                _ => KeyEventResponse::default(),
+            };
         }
 
-        // This call starts and stops long-press timers as well.
+        // This call starts and stops long-press timers as well, except for
+        // keys in a chord, which are tracked the way keys in a menu are.
         // It returns nothing if the handler should take no further action.
-        let Some(tracked) = self.create_or_update_tracked(key, button, false) else {
+        let in_chord = !matches!(chord, ChordOutcome::None);
+        let tracked = self.create_or_update_tracked(key, button, in_chord);
+        match chord {
+            ChordOutcome::Fired(action) => {
+                // Keys held before the chord finished may have long presses waiting.
+                for key in self.chords.consumed().to_vec() {
+                    if let Some(held) = self.tracked_keys.get(&key) {
+                        stop_long_press_timer(held.action());
+                    }
+                }
+                return self.handle_chord(action);
+            }
+            ChordOutcome::Swallowed => return KeyEventResponse::handled(),
+            ChordOutcome::None => {}
+        }
+        let Some(tracked) = tracked else {
             return KeyEventResponse::default();
         };
 
@@ -337,6 +997,10 @@ impl Controller {
 
         let options = settings();
         let requested_action = tracked.action();
+        // Any other HUD hotkey interrupts a playing macro.
+        if !matches!(requested_action, Action::Macro) && self.stop_macro() {
+            log::info!("Macro interrupted by {requested_action:?}.");
+        }
         // if the hud is NOT visible, we show it briefly before cycling.
         if !matches!(requested_action, Action::RefreshLayout | Action::ShowHide) && showBriefly() {
             return KeyEventResponse::handled();
@@ -368,6 +1032,27 @@ impl Controller {
                 Layout::refresh();
                 KeyEventResponse::handled()
             }
+            Action::Undo => {
+                self.undo_edit();
+                KeyEventResponse::handled()
+            }
+            Action::Redo => {
+                self.redo_edit();
+                KeyEventResponse::handled()
+            }
+            Action::EquipSetSwap => {
+                self.swap_to_previous_equipset();
+                KeyEventResponse::handled()
+            }
+            Action::Quickslot => self.use_quickslot(tracked.key),
+            Action::MacroRecord => {
+                self.toggle_macro_recording();
+                KeyEventResponse::handled()
+            }
+            Action::Macro => {
+                self.handle_macro_key(tracked.key);
+                KeyEventResponse::handled()
+            }
             Action::ShowHide => {
                 if !options.autofade() {
                     self.cycles.toggle_hud();
@@ -378,6 +1063,54 @@ impl Controller {
         }
     }
 
+    /// Do what a chord or sequence is bound to. Bindings act like a dedicated
+    /// hotkey: cycles advance, and modifiers and long presses don't apply.
+    fn handle_chord(&mut self, action: Action) -> KeyEventResponse {
+        log::debug!("chord or sequence fired for {action:?}");
+        if !matches!(action, Action::RefreshLayout | Action::ShowHide) && showBriefly() {
+            return KeyEventResponse::handled();
+        }
+
+        match action {
+            Action::Power => self.advance_cycle_power(),
+            Action::Utility => self.advance_cycle_utilities(),
+            Action::Left => {
+                if settings().cycle_ammo() && hasRangedEquipped() {
+                    self.advance_ammo()
+                } else {
+                    self.advance_hand_cycle(&CycleSlot::Left)
+                }
+            }
+            Action::Right => self.advance_hand_cycle(&CycleSlot::Right),
+            Action::Equipment => self.advance_cycle_equipset(),
+            Action::Activate => self.use_utility_item(),
+            Action::UnequipHands => self.disarm_player(),
+            Action::RefreshLayout => {
+                Layout::refresh();
+                KeyEventResponse::handled()
+            }
+            Action::Undo => {
+                self.undo_edit();
+                KeyEventResponse::handled()
+            }
+            Action::Redo => {
+                self.redo_edit();
+                KeyEventResponse::handled()
+            }
+            Action::EquipSetSwap => {
+                self.swap_to_previous_equipset();
+                KeyEventResponse::handled()
+            }
+            Action::ShowHide => {
+                if !settings().autofade() {
+                    self.cycles.toggle_hud();
+                }
+                KeyEventResponse::handled()
+            }
+            _ => KeyEventResponse::default(),
+        }
+    }
+
     /// Handle the power/shouts key being pressed.
     fn handle_cycle_power(&mut self) -> KeyEventResponse {
         let options = settings();
@@ -391,6 +1124,11 @@ impl Controller {
                     return self.advance_cycle_power();
                 }
             }
+            ActivationMethod::DoubleTap => {
+                if self.tracked_key(&Hotkey::Power).is_double_tap() {
+                    return self.advance_cycle_power();
+                }
+            }
         }
 
         KeyEventResponse::default()
@@ -406,12 +1144,17 @@ impl Controller {
         let candidate = if current_not_in_cycle {
             self.cycles.get_top(&CycleSlot::Power)
         } else {
-            self.cycles.advance(&CycleSlot::Power, 1)
+            let direction = self.cycle_direction();
+            self.maybe_return_to_pinned(&CycleSlot::Power, direction);
+            let filter = AdvanceFilter::from_settings(&settings());
+            self.cycles
+                .advance_usable(&CycleSlot::Power, direction, &filter, &mut self.cache)
         };
 
         if let Some(next) = candidate {
             let item = self.cache.get_with_refresh(&next);
             self.update_slot(HudElement::Power, &item);
+            self.macros.record(MacroStep::Advance(CycleSlot::Power));
             KeyEventResponse {
                 handled: true,
                 start_timer: Action::from(CycleSlot::Power),
@@ -426,19 +1169,28 @@ impl Controller {
     fn handle_cycle_utility(&mut self) -> KeyEventResponse {
         // Same comment about long presses.
         let options = settings();
+        let double_tap = self.tracked_key(&Hotkey::Utility).is_double_tap();
+        let cycle_method = options.cycle_advance_method();
 
-        if matches!(
-            options.utility_activation_method(),
-            ActivationMethod::Modifier
-        ) {
-            let modifier = self.tracked_modifier(&Modifier::Activate);
-            if modifier.is_pressed() {
+        match options.utility_activation_method() {
+            ActivationMethod::Modifier => {
+                let modifier = self.tracked_modifier(&Modifier::Activate);
+                if modifier.is_pressed() {
+                    log::debug!("activating utilities/consumables");
+                    return self.use_utility_item();
+                }
+            }
+            ActivationMethod::DoubleTap if double_tap => {
+                // The first tap cycled away from the item the player meant to use.
+                if matches!(cycle_method, ActivationMethod::Hotkey) {
+                    self.step_back_utilities();
+                }
                 log::debug!("activating utilities/consumables");
                 return self.use_utility_item();
             }
+            _ => {}
         }
 
-        let cycle_method = options.cycle_advance_method();
         if matches!(cycle_method, ActivationMethod::Hotkey) {
             log::debug!("cycling utilities/consumables");
             return self.advance_cycle_utilities();
@@ -450,6 +1202,10 @@ impl Controller {
                 return self.advance_cycle_utilities();
             }
         }
+        if matches!(cycle_method, ActivationMethod::DoubleTap) && double_tap {
+            log::debug!("cycling utilities/consumables");
+            return self.advance_cycle_utilities();
+        }
 
         KeyEventResponse::default()
     }
@@ -464,12 +1220,17 @@ impl Controller {
         let candidate = if current_not_in_cycle {
             self.cycles.get_top(&CycleSlot::Utility)
         } else {
-            self.cycles.advance(&CycleSlot::Utility, 1)
+            let direction = self.cycle_direction();
+            self.maybe_return_to_pinned(&CycleSlot::Utility, direction);
+            let filter = AdvanceFilter::from_settings(&settings());
+            self.cycles
+                .advance_usable(&CycleSlot::Utility, direction, &filter, &mut self.cache)
         };
 
         if let Some(next) = candidate {
             let item = self.cache.get_with_refresh(&next);
             self.update_slot(HudElement::Utility, &item);
+            self.macros.record(MacroStep::Advance(CycleSlot::Utility));
             KeyEventResponse {
                 handled: true,
                 start_timer: Action::None,
@@ -480,14 +1241,33 @@ impl Controller {
         }
     }
 
+    /// Undo a single step of the utilities cycle, for when a tap turns out to
+    /// be the first half of a double tap.
+    fn step_back_utilities(&mut self) {
+        let direction = match self.cycle_direction() {
+            Direction::Forward => Direction::Backward,
+            Direction::Backward => Direction::Forward,
+        };
+        let filter = AdvanceFilter::from_settings(&settings());
+        if let Some(previous) =
+            self.cycles
+                .advance_usable(&CycleSlot::Utility, direction, &filter, &mut self.cache)
+        {
+            let item = self.cache.get_with_refresh(&previous);
+            self.update_slot(HudElement::Utility, &item);
+        }
+    }
+
     /// Figure out what is supposed to happen on a key up, given settings and keystate.
     /// Only used for right and left hand.
     fn requested_keyup_action(&self, tracked: TrackedKey) -> RequestedAction {
         let options = settings();
         let is_long_press = tracked.is_long_press();
+        let is_double_tap = tracked.is_double_tap();
 
         let unequip_requested = match options.unequip_method() {
             UnarmedMethod::LongPress => is_long_press,
+            UnarmedMethod::DoubleTap => is_double_tap,
             UnarmedMethod::Modifier => {
                 let unequipmod = self.tracked_modifier(&Modifier::Unequip);
                 unequipmod.is_pressed()
@@ -512,6 +1292,7 @@ impl Controller {
                 let cyclemod = self.tracked_modifier(&Modifier::Cycle);
                 cyclemod.is_pressed()
             }
+            ActivationMethod::DoubleTap => is_double_tap,
         };
 
         if advance_requested {
@@ -568,8 +1349,7 @@ impl Controller {
             RequestedAction::Advance => self.advance_hand_cycle(&slot),
             RequestedAction::AdvanceAmmo => self.advance_ammo(), // pretty sure we never hit this
             RequestedAction::Match => self.match_hands(hand),
-            RequestedAction::Consume => KeyEventResponse::default(),
-            RequestedAction::None => KeyEventResponse::default(),
+            _ => KeyEventResponse::default(),
         }
     }
 
@@ -611,6 +1391,29 @@ impl Controller {
         }
     }
 
+    /// Cycle hotkeys move backward while the reverse modifier is held.
+    fn cycle_direction(&self) -> Direction {
+        if self.tracked_modifier(&Modifier::Reverse).is_pressed() {
+            Direction::Backward
+        } else {
+            Direction::Forward
+        }
+    }
+
+    /// If the player hasn't touched this cycle in a while, put its pinned
+    /// entry back up next so the coming advance lands on it.
+    fn maybe_return_to_pinned(&mut self, which: &CycleSlot, direction: Direction) {
+        let now = Instant::now();
+        let timeout = Duration::from_millis(settings().pin_timeout_ms() as u64);
+        let expired = self
+            .last_advanced
+            .insert(which.clone(), now)
+            .map_or(true, |then| now.duration_since(then) > timeout);
+        if expired {
+            self.cycles.return_to_pinned(which, direction);
+        }
+    }
+
     /// Advance the left or right hand cycle.
     fn advance_hand_cycle(&mut self, which: &CycleSlot) -> KeyEventResponse {
         // This is one of two tricky decision points in the mod. (The other
@@ -624,20 +1427,27 @@ impl Controller {
         } else {
             HudElement::Left
         };
+        let direction = self.cycle_direction();
+        let filter = AdvanceFilter::from_settings(&settings());
+        self.maybe_return_to_pinned(which, direction);
 
         if self.two_hander_equipped {
             // Here either hand may cycle, and the other hand must bounce back
             // to what was previously equipped. We update both slots in the HUD.
 
             // this should not be None given the first check, but we need to check anyway
-            let Some(form_string) = self.cycles.peek_next(which) else {
+            let Some(form_string) =
+                self.cycles
+                    .peek_next_usable(which, direction, &filter, &mut self.cache)
+            else {
                 return KeyEventResponse::handled();
             };
 
             let candidate = self.cache.get(&form_string);
             if self.treat_as_two_handed(&candidate) {
                 // no problem. just cycle to it.
-                self.cycles.advance(which, 1);
+                self.cycles
+                    .advance_usable(which, direction, &filter, &mut self.cache);
                 return self.update_and_record(which, &candidate);
             }
 
@@ -650,7 +1460,8 @@ impl Controller {
 
             if other_cached.is_empty() {
                 // The other hand has no opinions. Advance without fear.
-                self.cycles.advance(which, 1);
+                self.cycles
+                    .advance_usable(which, direction, &filter, &mut self.cache);
                 return self.update_and_record(which, &candidate);
             };
             let return_to = self.cache.get(&other_cached);
@@ -658,7 +1469,8 @@ impl Controller {
             // What do we want to return to? If it's completely different from us,
             // we are golden. We update both HUD slots and start a timer.
             if candidate.form_string() != return_to.form_string() {
-                self.cycles.advance(which, 1);
+                self.cycles
+                    .advance_usable(which, direction, &filter, &mut self.cache);
 
                 // are we bouncing back to something in a cycle or not? This is fun.
                 if self.cycles.includes(&other_hand, &return_to) {
@@ -689,7 +1501,8 @@ impl Controller {
 
             // They are the same. Do we have more than one? If so, we're good.
             if !candidate.count_matters() || candidate.count() > 1 {
-                self.cycles.advance(which, 1);
+                self.cycles
+                    .advance_usable(which, direction, &filter, &mut self.cache);
                 let _changed = &self.update_slot(other_hud, &return_to.clone());
                 self.cycles.set_top(&other_hand, &return_to.form_string());
                 return self.update_and_record(which, &candidate);
@@ -697,7 +1510,13 @@ impl Controller {
 
             // The worst case! Somebody's got to lose the battle for the single item,
             // and in this case it's the hand trying to cycle forward.
-            let Some(form_string) = self.cycles.advance_skipping(which, return_to.clone()) else {
+            let Some(form_string) = self.cycles.advance_skipping(
+                which,
+                return_to.clone(),
+                direction,
+                &filter,
+                &mut self.cache,
+            ) else {
                 honk();
                 return KeyEventResponse::handled();
             };
@@ -716,12 +1535,20 @@ impl Controller {
             let maybe_candidate = if let Some(other_equipped) = self.visible.get(&other_hud) {
                 // Are we dual-wielding? If so, do we have at least two?
                 if !other_equipped.count_matters() || other_equipped.count() > 1 {
-                    self.cycles.advance(which, 1)
+                    self.cycles
+                        .advance_usable(which, direction, &filter, &mut self.cache)
                 } else {
-                    self.cycles.advance_skipping(which, other_equipped.clone())
+                    self.cycles.advance_skipping(
+                        which,
+                        other_equipped.clone(),
+                        direction,
+                        &filter,
+                        &mut self.cache,
+                    )
                 }
             } else {
-                self.cycles.advance(which, 1)
+                self.cycles
+                    .advance_usable(which, direction, &filter, &mut self.cache)
             };
 
             if let Some(candidate) = maybe_candidate {
@@ -765,6 +1592,7 @@ impl Controller {
     fn update_and_record(&mut self, which: &CycleSlot, next: &HudItem) -> KeyEventResponse {
         let hud = HudElement::from(which);
         self.update_slot(hud, next);
+        self.macros.record(MacroStep::Advance(which.clone()));
 
         KeyEventResponse {
             handled: true,
@@ -781,30 +1609,7 @@ impl Controller {
     fn use_utility_item(&mut self) -> KeyEventResponse {
         if let Some(form_string) = self.cycles.get_top(&CycleSlot::Utility) {
             let item = self.cache.get(&form_string);
-            log::info!("Activating utility item: name='{}';", item.name());
-            if matches!(
-                item.kind(),
-                BaseType::Potion(PotionType::Poison) | BaseType::Food(_)
-            ) {
-                cxx::let_cxx_string!(form_spec = item.form_string());
-                consumePotion(&form_spec);
-            } else if item.form_string() == "health_proxy" {
-                chooseHealthPotion();
-            } else if item.form_string() == "magicka_proxy" {
-                chooseMagickaPotion();
-            } else if item.form_string() == "stamina_proxy" {
-                chooseStaminaPotion();
-            } else if item.is_potion() {
-                cxx::let_cxx_string!(form_spec = item.form_string());
-                consumePotion(&form_spec);
-            } else if item.is_armor() {
-                cxx::let_cxx_string!(form_spec = item.form_string());
-                cxx::let_cxx_string!(name = item.name());
-                toggleArmor(&form_spec, &name);
-            } else if item.is_ammo() {
-                cxx::let_cxx_string!(form_spec = item.form_string());
-                equipAmmo(&form_spec)
-            }
+            self.activate_utility(&item);
         } else {
             log::debug!("No item at top of utility cycle to use.");
         }
@@ -817,6 +1622,121 @@ impl Controller {
         }
     }
 
+    /// Drink, eat, apply, wear, or nock a utility item.
+    fn activate_utility(&mut self, item: &HudItem) {
+        log::info!("Activating utility item: name='{}';", item.name());
+        self.record_use(item.form_string().as_str());
+        self.macros.record(MacroStep::Use(item.form_string()));
+        if matches!(
+            item.kind(),
+            BaseType::Potion(PotionType::Poison) | BaseType::Food(_)
+        ) {
+            cxx::let_cxx_string!(form_spec = item.form_string());
+            consumePotion(&form_spec);
+        } else if item.form_string() == "health_proxy" {
+            chooseHealthPotion();
+        } else if item.form_string() == "magicka_proxy" {
+            chooseMagickaPotion();
+        } else if item.form_string() == "stamina_proxy" {
+            chooseStaminaPotion();
+        } else if item.is_potion() {
+            cxx::let_cxx_string!(form_spec = item.form_string());
+            consumePotion(&form_spec);
+        } else if item.is_armor() {
+            cxx::let_cxx_string!(form_spec = item.form_string());
+            cxx::let_cxx_string!(name = item.name());
+            toggleArmor(&form_spec, &name);
+        } else if item.is_ammo() {
+            cxx::let_cxx_string!(form_spec = item.form_string());
+            equipAmmo(&form_spec)
+        }
+    }
+
+    /// What the quickslot on this key is bound to. Bindings made in game win
+    /// over the settings file.
+    fn quickslot_target(&self, key: u32) -> Option<QuickslotTarget> {
+        let text = self
+            .cycles
+            .quickslot(key)
+            .or_else(|| settings().quickslot(key))?;
+        match text.parse::<QuickslotTarget>() {
+            Ok(target) => Some(target),
+            Err(e) => {
+                log::warn!("Quickslot key {key} has an unusable binding; {e:#}");
+                None
+            }
+        }
+    }
+
+    /// Use or equip whatever a quickslot key is bound to, right now. No cycle
+    /// moves; the HUD slot the item lands in shows it.
+    fn use_quickslot(&mut self, key: u32) -> KeyEventResponse {
+        let Some(target) = self.quickslot_target(key) else {
+            log::debug!("Quickslot key {key} isn't bound to anything yet.");
+            return KeyEventResponse::handled();
+        };
+        let form_spec = match &target {
+            QuickslotTarget::Item(spec) => Some(spec.clone()),
+            QuickslotTarget::Entry(slot, index) => self.cycles.formids(slot).get(*index).cloned(),
+        };
+        let Some(form_spec) = form_spec else {
+            log::info!("Quickslot {target} is past the end of its cycle.");
+            return KeyEventResponse::handled();
+        };
+
+        let item = self.cache.get_with_refresh(&form_spec);
+        let is_proxy = matches!(item.kind(), BaseType::PotionProxy(_));
+        if !is_proxy && !player_has(&item) {
+            let mut vars = HashMap::new();
+            vars.insert("item".to_string(), item.name());
+            if let Ok(message) = strfmt(&translated_key(FMT_QUICKSLOT_MISSING), &vars) {
+                log::info!("{message}");
+                notify(&message);
+            }
+            return KeyEventResponse::handled();
+        }
+        log::debug!("quickslot {key} uses {target}; {item}");
+
+        if item.is_power() {
+            self.equip_now(&item, Action::Power);
+        } else if item.is_utility() {
+            self.activate_utility(&item);
+        } else if item.is_ammo() {
+            cxx::let_cxx_string!(form_spec = item.form_string());
+            equipAmmo(&form_spec);
+            self.record_use(item.form_string().as_str());
+            self.update_slot(HudElement::Ammo, &item);
+        } else if item.is_armor() {
+            self.equip_item(&item, Action::Utility);
+            self.record_use(item.form_string().as_str());
+        } else {
+            let which = match target {
+                QuickslotTarget::Entry(CycleSlot::Left, _) => Action::Left,
+                _ if item.left_hand_ok() && !item.right_hand_ok() => Action::Left,
+                _ => Action::Right,
+            };
+            self.equip_now(&item, which);
+        }
+
+        KeyEventResponse::handled()
+    }
+
+    /// The player pressed a quickslot key while hovering over this item in a
+    /// menu. Bind the key to the item for this character.
+    pub fn bind_quickslot(&mut self, key: u32, item: HudItem) {
+        if matches!(item.kind(), BaseType::Empty) {
+            return;
+        }
+        self.cycles.set_quickslot(key, item.form_string());
+        let mut vars = HashMap::new();
+        vars.insert("item".to_string(), item.name());
+        vars.insert("key".to_string(), key.to_string());
+        if let Ok(message) = strfmt(&translated_key(FMT_QUICKSLOT_BOUND), &vars) {
+            log::info!("{message}");
+            notify(&message);
+        }
+    }
+
     /// When the equip delay for a cycle expires, equip the item at the top.
     ///
     /// This function implements a critical behavior in the mod: equipping
@@ -829,6 +1749,11 @@ impl Controller {
     /// We do not act here on cascading changes. Instead, we let the equipped-change
     /// callback decide what to do when, e.g., a two-handed item is equipped.
     pub fn timer_expired(&mut self, which: Action) {
+        if matches!(which, Action::MacroStep) {
+            self.play_next_macro_step();
+            return;
+        }
+
         // Has a long press action timer fired? If so, we do the long press action
         // for this key. We know there's one because we would not have started a
         // timer if there wasn't.
@@ -838,6 +1763,7 @@ impl Controller {
                 | Action::LongPressPower
                 | Action::LongPressRight
                 | Action::LongPressUtility
+                | Action::LongPressEquipSet
         ) {
             self.handle_long_press(which);
             return;
@@ -881,66 +1807,118 @@ impl Controller {
             return;
         }
 
+        let item = (*item).clone();
+        self.equip_now(&item, which);
+    }
+
+    /// Equip a power or a hand item right away and show it in its HUD slot.
+    fn equip_now(&mut self, item: &HudItem, which: Action) {
         if matches!(which, Action::Power) {
             // Equip that fus-ro-dah, dovahkin!
-            if let BaseType::Shout(t) = kind {
+            if let BaseType::Shout(t) = item.kind() {
                 log::info!("{}", t.translation());
             }
             cxx::let_cxx_string!(form_spec = item.form_string());
             equipShout(&form_spec);
-            return;
-        }
-
-        if !item.two_handed() {
-            if which == Action::Left {
-                self.left_hand_cached = item.form_string();
-            } else {
-                self.right_hand_cached = item.form_string();
+        } else {
+            if !item.two_handed() {
+                if which == Action::Left {
+                    self.left_hand_cached = item.form_string();
+                } else {
+                    self.right_hand_cached = item.form_string();
+                }
             }
+            self.equip_item(item, which);
         }
-        self.equip_item(item, which);
+        self.record_use(item.form_string().as_str());
+        self.update_slot(HudElement::from(which), item);
+        if let Ok(slot) = CycleSlot::try_from(which) {
+            self.macros
+                .record(MacroStep::Equip(slot, item.form_string()));
+        }
+    }
+
+    /// Count a use of this item toward its usage stats, then re-sort any
+    /// cycles the player wants kept in usage order.
+    fn record_use(&mut self, form_spec: &str) {
+        self.cycles.record_use(form_spec);
+        self.cycles.resort(&mut self.cache);
     }
 
-    /// Handle a long-press timer firing.
+    /// Handle a long-press timer firing. Each hotkey's long press does
+    /// whatever the settings bind it to.
     fn handle_long_press(&mut self, which: Action) {
-        match which {
-            Action::LongPressLeft => {
-                let do_this = Hotkey::Left.long_press_action();
-                if !matches!(do_this, RequestedAction::None) {
-                    self.do_hand_action(do_this, Action::Left, CycleSlot::Left);
-                    stopTimer(Action::Left);
-                }
+        let (hotkey, action) = match which {
+            Action::LongPressLeft => (Hotkey::Left, Action::Left),
+            Action::LongPressRight => (Hotkey::Right, Action::Right),
+            Action::LongPressPower => (Hotkey::Power, Action::Power),
+            Action::LongPressUtility => (Hotkey::Utility, Action::Utility),
+            Action::LongPressEquipSet => (Hotkey::Equipment, Action::Equipment),
+            _ => return,
+        };
+        let do_this = hotkey.long_press_action();
+        if matches!(do_this, RequestedAction::None) {
+            return;
+        }
+        log::debug!("long press of {hotkey}: {do_this}");
+        // Any pending equip for this key is moot now.
+        stopTimer(action);
+
+        match (do_this, action) {
+            (
+                requested @ (RequestedAction::Advance
+                | RequestedAction::AdvanceAmmo
+                | RequestedAction::Match
+                | RequestedAction::Unequip),
+                Action::Left,
+            ) => {
+                self.do_hand_action(requested, Action::Left, CycleSlot::Left);
             }
-            Action::LongPressRight => {
-                let do_this = Hotkey::Right.long_press_action();
-                if !matches!(do_this, RequestedAction::None) {
-                    self.do_hand_action(do_this, Action::Right, CycleSlot::Right);
-                    stopTimer(Action::Right);
-                }
+            (
+                requested @ (RequestedAction::Advance
+                | RequestedAction::AdvanceAmmo
+                | RequestedAction::Match
+                | RequestedAction::Unequip),
+                Action::Right,
+            ) => {
+                self.do_hand_action(requested, Action::Right, CycleSlot::Right);
             }
-            Action::LongPressPower => match Hotkey::Power.long_press_action() {
-                RequestedAction::Advance => {
-                    self.advance_cycle_power();
-                    stopTimer(Action::Power);
-                }
-                RequestedAction::Unequip => {
-                    unequipSlot(Action::Power);
-                    stopTimer(Action::Power);
-                }
-                _ => {}
-            },
-            Action::LongPressUtility => match Hotkey::Utility.long_press_action() {
-                RequestedAction::Advance => {
-                    self.handle_cycle_utility();
-                    stopTimer(Action::Utility);
+            (RequestedAction::Advance, Action::Power) => {
+                self.advance_cycle_power();
+            }
+            (RequestedAction::Advance, Action::Utility) => {
+                self.advance_cycle_utilities();
+            }
+            (RequestedAction::Advance, Action::Equipment) => {
+                self.advance_cycle_equipset();
+            }
+            (RequestedAction::AdvanceAmmo, _) => {
+                self.advance_ammo();
+            }
+            (RequestedAction::Unequip, Action::Power) => {
+                unequipSlot(Action::Power);
+            }
+            (RequestedAction::Consume, _) => {
+                self.use_utility_item();
+            }
+            (RequestedAction::ToggleHud, _) => {
+                if !settings().autofade() {
+                    self.cycles.toggle_hud();
                 }
-                RequestedAction::Consume => {
-                    self.use_utility_item();
-                    stopTimer(Action::Utility);
+            }
+            (RequestedAction::EquipSet(id), _) => {
+                if self.cycles.select_equipset_id(id).is_some() {
+                    self.equip_selected_set();
+                } else {
+                    log::info!("A long press asked for equipment set {id}, which doesn't exist.");
                 }
-                _ => {}
-            },
-            _ => {}
+            }
+            (RequestedAction::SwapEquipSet, _) => {
+                self.swap_to_previous_equipset();
+            }
+            (requested, _) => {
+                log::debug!("A long press of {hotkey} can't {requested}; ignoring.");
+            }
         }
     }
 
@@ -1328,6 +2306,11 @@ impl Controller {
             self.update_slot(HudElement::Utility, &HudItem::default());
         }
 
+        if let Some(equipset) = self.cycles.get_top_equipset() {
+            let set = self.equipset_hud_item(&equipset);
+            self.update_slot(HudElement::EquipSet, &set);
+        }
+
         log::info!(
             "HUD initialized. Now showing: power='{}'; left='{}'; right='{}'; ammo='{}';",
             power.name(),
@@ -1378,6 +2361,7 @@ impl Controller {
         log::debug!("handle_favorite_event(); is_favorite={is_favorite};");
         log::debug!("    {item}; two-handed={};", item.two_handed());
 
+        let before = self.cycles.snapshot();
         let maybe_message = if !is_favorite {
             // This formerly-favorite item is now disliked.
             let format = translated_key(FMT_ITEM_REMOVED);
@@ -1476,6 +2460,14 @@ impl Controller {
             }
         };
 
+        let label = if is_favorite {
+            format!("{} favorited", item.name())
+        } else {
+            format!("{} unfavorited", item.name())
+        };
+        self.cycles.resort(&mut self.cache);
+        self.remember_edit(label, before);
+
         if let Some(msg) = maybe_message {
             log::info!("{msg}");
             notify(&msg);
@@ -1492,7 +2484,7 @@ impl Controller {
         let Some(tracked) = self.create_or_update_tracked(key, button, false) else {
             return false;
         };
-        if !tracked.is_cycle_key() || !button.IsDown() {
+        if !(tracked.is_cycle_key() || tracked.is_quickslot()) || !button.IsDown() {
             return false;
         }
 
@@ -1513,6 +2505,7 @@ impl Controller {
                 );
                 modkey.is_pressed()
             }
+            ActivationMethod::DoubleTap => tracked.is_double_tap(),
         }
     }
 
@@ -1524,7 +2517,14 @@ impl Controller {
             return;
         };
 
+        let before = self.cycles.snapshot();
         let result = self.cycles.toggle(&cycle_slot, item.clone());
+        if matches!(
+            result,
+            MenuEventResponse::ItemAdded | MenuEventResponse::ItemAddedWithEvictions(_)
+        ) {
+            self.cycles.resort(&mut self.cache);
+        }
 
         if matches!(result, MenuEventResponse::ItemRemoved) && matches!(action, Action::Utility) {
             if let Some(topmost) = self.cycles.get_top(&CycleSlot::Utility) {
@@ -1537,7 +2537,9 @@ impl Controller {
 
         // notify the player what happened...
         let verb = match result {
-            MenuEventResponse::ItemAdded => translated_key(FMT_ITEM_ADDED),
+            MenuEventResponse::ItemAdded | MenuEventResponse::ItemAddedWithEvictions(_) => {
+                translated_key(FMT_ITEM_ADDED)
+            }
             MenuEventResponse::ItemRemoved => translated_key(FMT_ITEM_REMOVED),
             MenuEventResponse::ItemInappropriate => translated_key(FMT_ITEM_REJECTED),
             MenuEventResponse::TooManyItems => translated_key(FMT_ITEM_TOOMANY),
@@ -1554,7 +2556,20 @@ impl Controller {
         let mut vars = HashMap::new();
         vars.insert("item".to_string(), item.name());
         vars.insert("cycle".to_string(), cyclename);
-        if let Ok(message) = strfmt(&verb, &vars) {
+        let label = strfmt(&verb, &vars).unwrap_or_else(|_| item.name());
+        self.remember_edit(label, before);
+
+        if let MenuEventResponse::ItemAddedWithEvictions(evicted) = &result {
+            // Name each entry that made way for the new one.
+            let format = translated_key(FMT_ITEM_EVICTED);
+            for spec in evicted.iter() {
+                vars.insert("evicted".to_string(), self.cache.get(spec).name());
+                if let Ok(message) = strfmt(&format, &vars) {
+                    log::info!("{message}");
+                    notify(&message);
+                }
+            }
+        } else if let Ok(message) = strfmt(&verb, &vars) {
             log::info!("{}; kind={:?};", message, item.kind());
             notify(&message);
         } else {
@@ -1602,16 +2617,11 @@ impl Controller {
                     Action::Utility => startTimer(Action::LongPressUtility, duration),
                     Action::Left => startTimer(Action::LongPressLeft, duration),
                     Action::Right => startTimer(Action::LongPressRight, duration),
+                    Action::Equipment => startTimer(Action::LongPressEquipSet, duration),
                     _ => {}
                 }
             } else if matches!(tracked.state, KeyState::Up) {
-                match action {
-                    Action::Power => stopTimer(Action::LongPressPower),
-                    Action::Utility => stopTimer(Action::LongPressUtility),
-                    Action::Left => stopTimer(Action::LongPressLeft),
-                    Action::Right => stopTimer(Action::LongPressRight),
-                    _ => {}
-                }
+                stop_long_press_timer(action);
             }
         }
 
@@ -1649,7 +2659,7 @@ impl Controller {
     // ----------- equipment set functions
 
     /// Handle the power/shouts key being pressed.
-    fn handle_cycle_equipset(&mut self, _tracked: TrackedKey) -> KeyEventResponse {
+    fn handle_cycle_equipset(&mut self, tracked: TrackedKey) -> KeyEventResponse {
         let options = settings();
         let cycle_method = options.cycle_advance_method();
 
@@ -1663,6 +2673,11 @@ impl Controller {
                     return self.advance_cycle_equipset();
                 }
             }
+            ActivationMethod::DoubleTap => {
+                if tracked.is_double_tap() {
+                    return self.advance_cycle_equipset();
+                }
+            }
         }
 
         KeyEventResponse::default()
@@ -1670,8 +2685,13 @@ impl Controller {
 
     /// Rotate to the next equipment set in the cycle and start the timer.
     fn advance_cycle_equipset(&mut self) -> KeyEventResponse {
-        let candidate = self.cycles.advance_equipset(1);
-        if let Some(_next) = candidate {
+        let candidate = match self.cycle_direction() {
+            Direction::Forward => self.cycles.advance_equipset(1),
+            Direction::Backward => self.cycles.retreat_equipset(1),
+        };
+        if let Some(next) = candidate {
+            let set = self.equipset_hud_item(&next);
+            self.update_slot(HudElement::EquipSet, &set);
             KeyEventResponse {
                 handled: true,
                 start_timer: Action::Equipment,
@@ -1685,31 +2705,274 @@ impl Controller {
         }
     }
 
-    /// Called when equipment timer expires: equip that equip set!
+    /// Called when equipment timer expires: equip that equip set! Only the
+    /// parts the set records are changed, and only where they differ from what
+    /// the player has on. Anything the player no longer has is reported instead
+    /// of equipped.
     fn equip_selected_set(&mut self) {
-        let Some(equipset) = self.cycles.get_top_equipset() else {
+        let Some(equipset) = self
+            .cycles
+            .get_top_equipset()
+            .and_then(|top| self.cycles.resolve_equipset(top.id(), &armor_slot_mask))
+        else {
             return;
         };
         log::debug!("Switching to equipment set '{}'.", equipset.name());
-        if settings().equip_sets_unequip() {
-            equipset.empty_slots().iter().for_each(|shift| {
-                unequipSlotByShift(*shift);
-            });
+        let mut missing: Vec<String> = Vec::new();
+
+        let equipped = getEquippedItems();
+        let current = CurrentGear {
+            items: equipped.items.clone(),
+            empty_slots: equipped.empty_slots.clone(),
+            power: self.equipped_part(SetPart::Power),
+            ammo: self.equipped_part(SetPart::Ammo),
+            left: self.equipped_part(SetPart::Left),
+            right: self.equipped_part(SetPart::Right),
+        };
+        let diff = equipset.diff(&current, settings().equip_sets_unequip());
+        if diff.is_empty() {
+            log::debug!("The player already has this set on.");
         }
-        equipset.items().iter().for_each(|item| {
+
+        diff.unequip.iter().for_each(|shift| {
+            unequipSlotByShift(*shift);
+        });
+        diff.equip.iter().for_each(|item| {
             let cached = self.cache.get(item);
+            if !player_has(&cached) {
+                missing.push(cached.name());
+                return;
+            }
             let_cxx_string!(form_spec = item.identifier());
             let_cxx_string!(name = cached.name());
             equipArmor(&form_spec, &name);
         });
 
-        let set = HudItem::for_equip_set(equipset.name(), equipset.id(), equipset.icon.clone());
+        for part in diff.parts {
+            let Some(spec) = equipset.part(part) else {
+                continue;
+            };
+            let cached = self.cache.get(spec);
+            if !player_has(&cached) {
+                log::info!(
+                    "Equipment set '{}' can't equip its {part}; spec={spec};",
+                    equipset.name()
+                );
+                missing.push(cached.name());
+                continue;
+            }
+            let_cxx_string!(form_spec = spec.as_str());
+            match part {
+                SetPart::Power => equipShout(&form_spec),
+                SetPart::Ammo => equipAmmo(&form_spec),
+                SetPart::Left => {
+                    self.left_hand_cached = spec.clone();
+                    self.equip_item(&cached, Action::Left);
+                }
+                SetPart::Right => {
+                    self.right_hand_cached = spec.clone();
+                    self.equip_item(&cached, Action::Right);
+                }
+                SetPart::Armor => {}
+            }
+        }
+
+        if !missing.is_empty() {
+            let mut vars = HashMap::new();
+            vars.insert("name".to_string(), equipset.name());
+            vars.insert("missing".to_string(), missing.join(", "));
+            if let Ok(message) = strfmt(&translated_key(FMT_SET_INCOMPLETE), &vars) {
+                notify(&message);
+            }
+        }
+
+        if self.current_equipset != Some(equipset.id()) {
+            self.previous_equipset = self.current_equipset.replace(equipset.id());
+        }
+        self.macros.record(MacroStep::EquipSet(equipset.id()));
+        let set = self.equipset_hud_item(&equipset);
         self.update_slot(HudElement::EquipSet, &set);
     }
 
+    /// Build the HUD entry for an equipment set, tinted if the player no
+    /// longer has everything in it, including what it inherits.
+    fn equipset_hud_item(&mut self, equipset: &EquipSet) -> HudItem {
+        let mut set = HudItem::for_equip_set(equipset.name(), equipset.id(), equipset.icon.clone());
+        let resolved = self
+            .cycles
+            .resolve_equipset(equipset.id(), &armor_slot_mask)
+            .unwrap_or_else(|| equipset.clone());
+        let missing = resolved.missing(|spec| {
+            let item = self.cache.get(&spec.to_string());
+            player_has(&item)
+        });
+        if !missing.is_empty() {
+            set.mark_broken();
+        }
+        set
+    }
+
+    /// Re-equip the set the player had on before the current one, right away.
+    /// Swapping twice gets the current set back, like alt-tab for outfits.
+    fn swap_to_previous_equipset(&mut self) {
+        let Some(id) = self.previous_equipset else {
+            log::debug!("No previous equipment set to swap back to.");
+            return;
+        };
+        let Some(set) = self.cycles.select_equipset_id(id) else {
+            log::info!("The previous equipment set is gone; id={id};");
+            self.previous_equipset = None;
+            return;
+        };
+        log::debug!("Swapping back to equipment set '{}'.", set.name());
+        self.equip_selected_set();
+    }
+
+    /// Start recording a macro, or throw away the one being recorded.
+    fn toggle_macro_recording(&mut self) {
+        let key = if self.macros.finish_recording().is_some() {
+            FMT_MACRO_CANCELLED
+        } else {
+            self.stop_macro();
+            self.macros.start_recording();
+            FMT_MACRO_RECORDING
+        };
+        let message = translated_key(key);
+        log::info!("{message}");
+        notify(&message);
+    }
+
+    /// A macro key does one of three things: saves the macro being recorded
+    /// on itself, stops its macro if it's playing, or plays its macro.
+    fn handle_macro_key(&mut self, key: u32) {
+        let name = settings().macro_name(key);
+        let mut vars = HashMap::new();
+        vars.insert("name".to_string(), name.clone());
+
+        if let Some(steps) = self.macros.finish_recording() {
+            vars.insert("count".to_string(), steps.len().to_string());
+            log::debug!(
+                "Macro '{name}' on key {key}: {}",
+                steps
+                    .iter()
+                    .map(|xs| xs.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
+            self.cycles.set_macro(key, steps);
+            if let Ok(message) = strfmt(&translated_key(FMT_MACRO_SAVED), &vars) {
+                log::info!("{message}");
+                notify(&message);
+            }
+            return;
+        }
+
+        if self.stop_macro() {
+            log::info!("Macro '{name}' stopped by the player.");
+            return;
+        }
+
+        let Some(steps) = self.cycles.macro_steps(key).filter(|xs| !xs.is_empty()) else {
+            if let Ok(message) = strfmt(&translated_key(FMT_MACRO_EMPTY), &vars) {
+                notify(&message);
+            }
+            return;
+        };
+        log::info!("Playing macro '{name}'; {} steps.", steps.len());
+        self.macros.start_playback(steps);
+        self.play_next_macro_step();
+    }
+
+    /// Take one step of the playing macro, then wait before the next one.
+    fn play_next_macro_step(&mut self) {
+        let Some(step) = self.macros.next_step() else {
+            return;
+        };
+        log::debug!("macro step: {step}");
+        match step {
+            MacroStep::Advance(slot) => {
+                // The steps that follow say what to equip, so we drop the
+                // equip delay the advance asks for.
+                match slot {
+                    CycleSlot::Power => self.advance_cycle_power(),
+                    CycleSlot::Utility => self.advance_cycle_utilities(),
+                    CycleSlot::Left | CycleSlot::Right => self.advance_hand_cycle(&slot),
+                };
+            }
+            MacroStep::Equip(slot, spec) => {
+                let item = self.cache.get_with_refresh(&spec);
+                if player_has(&item) {
+                    self.equip_now(&item, Action::from(slot));
+                } else {
+                    log::info!(
+                        "Macro skipped equipping {}; the player lacks it.",
+                        item.name()
+                    );
+                }
+            }
+            MacroStep::Use(spec) => {
+                let item = self.cache.get_with_refresh(&spec);
+                if matches!(item.kind(), BaseType::PotionProxy(_)) || player_has(&item) {
+                    self.activate_utility(&item);
+                } else {
+                    log::info!("Macro skipped using {}; the player lacks it.", item.name());
+                }
+            }
+            MacroStep::EquipSet(id) => {
+                if self.cycles.select_equipset_id(id).is_some() {
+                    self.equip_selected_set();
+                } else {
+                    log::info!("Macro skipped equipment set {id}, which no longer exists.");
+                }
+            }
+        }
+
+        self.macros.step_done();
+        if self.macros.is_playing() {
+            startTimer(Action::MacroStep, settings().macro_step_ms());
+        }
+    }
+
+    /// Stop a playing macro. Returns true if one was playing.
+    fn stop_macro(&mut self) -> bool {
+        if self.macros.stop_playback() {
+            stopTimer(Action::MacroStep);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Papyrus support. Describe each item the player no longer has from this
+    /// set, including anything it inherits.
+    pub fn get_equipset_problems(&mut self, id: u32) -> Vec<String> {
+        let Some(set) = self.cycles.resolve_equipset(id, &armor_slot_mask) else {
+            return Vec::new();
+        };
+        let missing = set.missing(|spec| {
+            let item = self.cache.get(&spec.to_string());
+            player_has(&item)
+        });
+        let template = translated_key(FMT_SET_PROBLEM);
+        missing
+            .into_iter()
+            .filter_map(|(part, spec)| {
+                let name = self.cache.get(&spec).name();
+                let mut vars = HashMap::new();
+                vars.insert(
+                    "item".to_string(),
+                    if name.is_empty() { spec } else { name },
+                );
+                vars.insert("part".to_string(), part.to_string());
+                strfmt(&template, &vars).ok()
+            })
+            .collect()
+    }
+
     /// Called by the MCM code when it is showing a list of all items in an equipment set.
+    /// Sets based on another list everything they inherit, too.
     pub fn get_equipset_item_names(&mut self, id: u32) -> Vec<String> {
-        if let Some(set) = self.cycles.equipset_by_id(id) {
+        if let Some(set) = self.cycles.resolve_equipset(id, &armor_slot_mask) {
             set.items
                 .iter()
                 .map(|xs| {
@@ -1726,7 +2989,7 @@ impl Controller {
     /// equipment set. The named item should be in the given equip set, but we
     /// don't enforce that.
     pub fn set_equipset_icon(&mut self, id: u32, itemname: String) -> bool {
-        let Some(set) = self.cycles.equipset_by_id(id) else {
+        let Some(set) = self.cycles.resolve_equipset(id, &armor_slot_mask) else {
             return false;
         };
 
@@ -1746,6 +3009,34 @@ impl Controller {
     }
 }
 
+/// The biped slots an armor form covers, as a bit mask. Used to decide which
+/// parent armor an override replaces.
+fn armor_slot_mask(form_spec: &str) -> u32 {
+    let_cxx_string!(form_spec = form_spec);
+    armorSlotMask(&form_spec)
+}
+
+/// Stop the long-press timer for a hotkey's action, if it has one.
+fn stop_long_press_timer(action: Action) {
+    match action {
+        Action::Power => stopTimer(Action::LongPressPower),
+        Action::Utility => stopTimer(Action::LongPressUtility),
+        Action::Left => stopTimer(Action::LongPressLeft),
+        Action::Right => stopTimer(Action::LongPressRight),
+        Action::Equipment => stopTimer(Action::LongPressEquipSet),
+        _ => {}
+    }
+}
+
+/// Does the player still have this item or know this spell?
+fn player_has(item: &HudItem) -> bool {
+    if matches!(item.kind(), BaseType::Empty) {
+        return false;
+    }
+    let_cxx_string!(form_spec = item.form_string());
+    hasItemOrSpell(&form_spec) || itemCount(&form_spec) > 0
+}
+
 impl Default for KeyEventResponse {
     fn default() -> Self {
         Self {
@@ -1789,6 +3080,18 @@ impl From<u32> for Action {
             Action::Equipment
         } else if value == settings.unequip_hotkey() as u32 {
             Action::UnequipHands
+        } else if value == settings.undo_key() as u32 {
+            Action::Undo
+        } else if value == settings.redo_key() as u32 {
+            Action::Redo
+        } else if value == settings.equipset_swap_key() as u32 {
+            Action::EquipSetSwap
+        } else if value == settings.macro_record_key() as u32 {
+            Action::MacroRecord
+        } else if settings.is_quickslot_key(value) {
+            Action::Quickslot
+        } else if settings.is_macro_key(value) {
+            Action::Macro
         } else {
             Action::None
         }
@@ -1806,6 +3109,8 @@ pub enum MenuEventResponse {
     ItemRemoved,
     ItemInappropriate,
     TooManyItems,
+    /// The item went in after these entries were evicted to make room.
+    ItemAddedWithEvictions(Vec<String>),
 }
 
 /// TODO: derivable?
@@ -1842,6 +3147,21 @@ const FMT_ITEM_ADDED: &str = "$SoulsyHUD_fmt_ItemAdded";
 const FMT_ITEM_REJECTED: &str = "$SoulsyHUD_fmt_ItemRejected";
 const FMT_ITEM_TOOMANY: &str = "$SoulsyHUD_fmt_TooMany";
 const FMT_ITEM_NOCHANGE: &str = "$SoulsyHUD_fmt_NoChange";
+const FMT_ITEM_EVICTED: &str = "$SoulsyHUD_fmt_ItemEvicted";
+const FMT_SET_INCOMPLETE: &str = "$SoulsyHUD_fmt_EquipSetIncomplete";
+const FMT_SET_PROBLEM: &str = "$SoulsyHUD_fmt_EquipSetProblem";
+const FMT_QUICKSLOT_BOUND: &str = "$SoulsyHUD_fmt_QuickslotBound";
+const FMT_QUICKSLOT_MISSING: &str = "$SoulsyHUD_fmt_QuickslotMissing";
+const FMT_MACRO_RECORDING: &str = "$SoulsyHUD_MacroRecording";
+const FMT_MACRO_CANCELLED: &str = "$SoulsyHUD_MacroCancelled";
+const FMT_MACRO_SAVED: &str = "$SoulsyHUD_fmt_MacroSaved";
+const FMT_MACRO_EMPTY: &str = "$SoulsyHUD_fmt_MacroEmpty";
+const FMT_LOADOUT_EXPORTED: &str = "$SoulsyHUD_fmt_LoadoutExported";
+const FMT_LOADOUT_IMPORTED: &str = "$SoulsyHUD_fmt_LoadoutImported";
+const FMT_LOADOUT_MISSING: &str = "$SoulsyHUD_fmt_LoadoutMissing";
+const FMT_UNDONE: &str = "$SoulsyHUD_fmt_Undone";
+const FMT_REDONE: &str = "$SoulsyHUD_fmt_Redone";
+const FMT_AUTO_FILLED: &str = "$SoulsyHUD_fmt_AutoFilled";
 const FMT_ITEM_POWERS_CYCLE: &str = "$SoulsyHUD_fmt_PowersCycle";
 const FMT_ITEM_UTILITIES_CYCLE: &str = "$SoulsyHUD_fmt_UtilitiesCycle";
 const FMT_ITEM_LEFT_CYCLE: &str = "$SoulsyHUD_fmt_LeftHandCycle";
@@ -1851,11 +3171,60 @@ const FMT_ITEM_BOTH_HANDS: &str = "$SoulsyHUD_fmt_BothHands";
 /// Possible actions requested when a user presses a cycle key.
 /// The action is determined using the key pressed, the presence of modifiers,
 /// and various user settings.
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum RequestedAction {
     Advance,
     AdvanceAmmo,
     Consume,
     Match,
     Unequip,
+    /// Show or hide the HUD.
+    ToggleHud,
+    /// Equip the equipment set with this id.
+    EquipSet(u32),
+    /// Swap back to the previously equipped equipment set.
+    SwapEquipSet,
     None,
 }
+
+impl FromStr for RequestedAction {
+    type Err = eyre::Report;
+
+    /// Parse a long-press binding from the settings file, e.g. `advance` or `equipset:3`.
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let value = value.trim().to_lowercase();
+        if let Some(id) = value.strip_prefix("equipset:") {
+            let Ok(id) = id.trim().parse::<u32>() else {
+                return Err(eyre::eyre!("'{id}' is not an equipment set id"));
+            };
+            return Ok(RequestedAction::EquipSet(id));
+        }
+        match value.as_str() {
+            "advance" => Ok(RequestedAction::Advance),
+            "advance_ammo" => Ok(RequestedAction::AdvanceAmmo),
+            "consume" => Ok(RequestedAction::Consume),
+            "match" => Ok(RequestedAction::Match),
+            "unequip" => Ok(RequestedAction::Unequip),
+            "toggle_hud" => Ok(RequestedAction::ToggleHud),
+            "swap_equipset" => Ok(RequestedAction::SwapEquipSet),
+            "none" => Ok(RequestedAction::None),
+            _ => Err(eyre::eyre!("unknown long-press action '{value}'")),
+        }
+    }
+}
+
+impl std::fmt::Display for RequestedAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RequestedAction::Advance => write!(f, "advance"),
+            RequestedAction::AdvanceAmmo => write!(f, "advance_ammo"),
+            RequestedAction::Consume => write!(f, "consume"),
+            RequestedAction::Match => write!(f, "match"),
+            RequestedAction::Unequip => write!(f, "unequip"),
+            RequestedAction::ToggleHud => write!(f, "toggle_hud"),
+            RequestedAction::EquipSet(id) => write!(f, "equipset:{id}"),
+            RequestedAction::SwapEquipSet => write!(f, "swap_equipset"),
+            RequestedAction::None => write!(f, "none"),
+        }
+    }
+}
@@ -3,6 +3,9 @@
 //! for `Vec<String>` and `Vec<EquipSet>`, along with some other traits for
 //! each of those.
 
+use strum::{Display, EnumString};
+
+use super::triggers::Trigger;
 use crate::data::base::BaseType;
 use crate::data::huditem::HudItem;
 use crate::data::item_cache::ItemCache;
@@ -21,6 +24,47 @@ pub struct EquipSet {
     pub empty: Vec<u8>,
     /// Which icon to use.
     pub icon: Icon,
+    /// The power or shout to equip, if this set changes it.
+    pub power: Option<String>,
+    /// The ammo to equip, if this set changes it.
+    pub ammo: Option<String>,
+    /// The spell or weapon for the left hand, if this set changes it.
+    pub left: Option<String>,
+    /// The spell or weapon for the right hand, if this set changes it.
+    pub right: Option<String>,
+    /// Conditions that equip this set automatically.
+    pub triggers: Vec<Trigger>,
+    /// Whether the triggers are switched on.
+    pub auto_equip: bool,
+    /// The id of the set this one is based on. A set with a parent holds only
+    /// its overrides; the full set is resolved when it's equipped.
+    pub parent: Option<u32>,
+}
+
+/// How long a chain of parents may be before we assume it loops.
+pub const MAX_INHERITANCE_DEPTH: usize = 8;
+
+/// The parts of an equipment set. Each part is optional: a set leaves a part
+/// of the player's gear alone unless it has something recorded for that part.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, Display, EnumString)]
+#[strum(serialize_all = "lowercase", ascii_case_insensitive)]
+pub enum SetPart {
+    /// Worn armor, plus the slots to leave empty.
+    Armor,
+    Power,
+    Ammo,
+    Left,
+    Right,
+}
+
+impl SetPart {
+    pub const ALL: [SetPart; 5] = [
+        SetPart::Armor,
+        SetPart::Power,
+        SetPart::Ammo,
+        SetPart::Left,
+        SetPart::Right,
+    ];
 }
 
 impl EquipSet {
@@ -39,6 +83,13 @@ impl EquipSet {
             items,
             empty,
             icon,
+            power: None,
+            ammo: None,
+            left: None,
+            right: None,
+            triggers: Vec::new(),
+            auto_equip: false,
+            parent: None,
         }
     }
 
@@ -54,6 +105,13 @@ impl EquipSet {
             items,
             empty,
             icon,
+            power: None,
+            ammo: None,
+            left: None,
+            right: None,
+            triggers: Vec::new(),
+            auto_equip: false,
+            parent: None,
         }
     }
 
@@ -87,6 +145,240 @@ impl EquipSet {
     pub fn icon(&self) -> &Icon {
         &self.icon
     }
+
+    /// Get the form spec recorded for a hand, power, or ammo part. Armor has
+    /// a list of items instead, so it's always None here.
+    pub fn part(&self, part: SetPart) -> Option<&String> {
+        match part {
+            SetPart::Armor => None,
+            SetPart::Power => self.power.as_ref(),
+            SetPart::Ammo => self.ammo.as_ref(),
+            SetPart::Left => self.left.as_ref(),
+            SetPart::Right => self.right.as_ref(),
+        }
+    }
+
+    /// Record a form spec for a hand, power, or ammo part, or None to leave
+    /// that part alone. Does nothing for armor.
+    pub fn set_part(&mut self, part: SetPart, form_spec: Option<String>) {
+        let form_spec = form_spec.filter(|xs| !xs.is_empty());
+        match part {
+            SetPart::Armor => {}
+            SetPart::Power => self.power = form_spec,
+            SetPart::Ammo => self.ammo = form_spec,
+            SetPart::Left => self.left = form_spec,
+            SetPart::Right => self.right = form_spec,
+        }
+    }
+
+    /// Does this set change the given part of the player's gear?
+    pub fn has_part(&self, part: SetPart) -> bool {
+        match part {
+            SetPart::Armor => !self.items.is_empty() || !self.empty.is_empty(),
+            _ => self.part(part).is_some(),
+        }
+    }
+
+    /// The parts of the player's gear this set changes.
+    pub fn parts(&self) -> Vec<SetPart> {
+        SetPart::ALL
+            .into_iter()
+            .filter(|part| self.has_part(*part))
+            .collect()
+    }
+
+    /// Every form spec in this set that the lookup can't find, paired with
+    /// the part it belongs to. Armor comes first, in the order it was added.
+    pub fn missing(&self, mut found: impl FnMut(&str) -> bool) -> Vec<(SetPart, String)> {
+        let armor = self.items.iter().map(|spec| (SetPart::Armor, spec));
+        let parts = SetPart::ALL
+            .into_iter()
+            .filter_map(|part| self.part(part).map(|spec| (part, spec)));
+        armor
+            .chain(parts)
+            .filter(|(_, spec)| !found(spec))
+            .map(|(part, spec)| (part, spec.clone()))
+            .collect()
+    }
+
+    /// Lay this set's overrides over a resolved parent set. An armor override
+    /// replaces any parent armor sharing a biped slot with it, and an empty
+    /// slot override takes off any parent armor in that slot. `slots` gives
+    /// the biped slot mask for an armor form spec. Hand, power, and ammo parts
+    /// this set doesn't record come from the parent.
+    pub fn overlay(&self, base: &EquipSet, slots: &impl Fn(&str) -> u32) -> EquipSet {
+        let mut items = base.items.clone();
+        let mut empty = base.empty.clone();
+        for spec in self.items.iter() {
+            let mask = slots(spec);
+            items.retain(|xs| xs != spec && slots(xs) & mask == 0);
+            empty.retain(|shift| mask & slot_bit(*shift) == 0);
+            items.push(spec.clone());
+        }
+        for shift in self.empty.iter() {
+            items.retain(|xs| slots(xs) & slot_bit(*shift) == 0);
+            if !empty.contains(shift) {
+                empty.push(*shift);
+            }
+        }
+
+        let mut resolved = self.clone();
+        resolved.items = items;
+        resolved.empty = empty;
+        for part in [SetPart::Power, SetPart::Ammo, SetPart::Left, SetPart::Right] {
+            if self.part(part).is_none() {
+                resolved.set_part(part, base.part(part).cloned());
+            }
+        }
+        resolved
+    }
+
+    /// Drop everything this set has in common with a resolved parent, so only
+    /// the overrides are left.
+    pub fn trim_to_overrides(&mut self, base: &EquipSet) {
+        self.items.retain(|xs| !base.items.contains(xs));
+        self.empty.retain(|xs| !base.empty.contains(xs));
+        for part in [SetPart::Power, SetPart::Ammo, SetPart::Left, SetPart::Right] {
+            if self.part(part) == base.part(part) {
+                self.set_part(part, None);
+            }
+        }
+    }
+
+    /// Work out what has to change to go from what the player has on to this
+    /// set. Items already worn are left alone, and empty slots are only
+    /// emptied if something is in them.
+    pub fn diff(&self, current: &CurrentGear, unequip_empty: bool) -> SetDiff {
+        let unequip = if unequip_empty {
+            self.empty
+                .iter()
+                .filter(|shift| !current.empty_slots.contains(shift))
+                .copied()
+                .collect()
+        } else {
+            Vec::new()
+        };
+        let equip = self
+            .items
+            .iter()
+            .filter(|spec| !current.items.contains(spec))
+            .cloned()
+            .collect();
+        let parts = [SetPart::Power, SetPart::Ammo, SetPart::Left, SetPart::Right]
+            .into_iter()
+            .filter(|part| {
+                self.part(*part)
+                    .is_some_and(|spec| spec.as_str() != current.part(*part))
+            })
+            .collect();
+        SetDiff {
+            unequip,
+            equip,
+            parts,
+        }
+    }
+}
+
+fn slot_bit(shift: u8) -> u32 {
+    1u32.checked_shl(shift as u32).unwrap_or(0)
+}
+
+/// Resolve the set with this id against its chain of parents. A parent that
+/// no longer exists ends the chain, as does a chain that loops back on itself.
+pub fn resolve_equipset(
+    sets: &[EquipSet],
+    id: u32,
+    slots: &impl Fn(&str) -> u32,
+) -> Option<EquipSet> {
+    let mut chain: Vec<&EquipSet> = Vec::new();
+    let mut next = Some(id);
+    while let Some(current) = next {
+        if chain.len() >= MAX_INHERITANCE_DEPTH || chain.iter().any(|xs| xs.id() == current) {
+            log::warn!("Equipment set {id} has a loop in its parents; ignoring the rest.");
+            break;
+        }
+        let Some(set) = sets.iter().find(|xs| xs.id() == current) else {
+            break;
+        };
+        chain.push(set);
+        next = set.parent;
+    }
+
+    let (root, children) = chain.split_last()?;
+    let resolved = children
+        .iter()
+        .rev()
+        .fold((*root).clone(), |base, child| child.overlay(&base, slots));
+    Some(resolved)
+}
+
+/// What the player has on right now, in the same terms an equipment set uses.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CurrentGear {
+    /// Form specs for worn armor.
+    pub items: Vec<String>,
+    /// Biped slots with nothing in them.
+    pub empty_slots: Vec<u8>,
+    /// Form specs for the rest; empty strings if nothing is equipped.
+    pub power: String,
+    pub ammo: String,
+    pub left: String,
+    pub right: String,
+}
+
+impl CurrentGear {
+    fn part(&self, part: SetPart) -> &str {
+        match part {
+            SetPart::Armor => "",
+            SetPart::Power => self.power.as_str(),
+            SetPart::Ammo => self.ammo.as_str(),
+            SetPart::Left => self.left.as_str(),
+            SetPart::Right => self.right.as_str(),
+        }
+    }
+}
+
+/// The equip and unequip calls needed to put on an equipment set.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SetDiff {
+    /// Biped slots to empty, as shifts.
+    pub unequip: Vec<u8>,
+    /// Armor to put on.
+    pub equip: Vec<String>,
+    /// Power, ammo, and hand parts that need changing.
+    pub parts: Vec<SetPart>,
+}
+
+impl SetDiff {
+    /// True if the player already has the set on.
+    pub fn is_empty(&self) -> bool {
+        self.unequip.is_empty() && self.equip.is_empty() && self.parts.is_empty()
+    }
+}
+
+/// Per-entry settings for an item in a cycle. Entries without any flags set
+/// are not tracked at all.
+#[derive(Debug, Clone, Default, Hash, PartialEq, Eq)]
+pub struct EntryFlags {
+    /// Advancing this cycle after a pause returns to this entry.
+    pub pinned: bool,
+    /// Skip over this entry when advancing if the player can't use it right now.
+    pub skip_unusable: bool,
+}
+
+impl EntryFlags {
+    /// True if this entry has no flags set and need not be remembered.
+    pub fn is_empty(&self) -> bool {
+        !self.pinned && !self.skip_unusable
+    }
+}
+
+/// Which way to move through a cycle.
+#[derive(Debug, Clone, Copy, Default, Hash, PartialEq, Eq)]
+pub enum Direction {
+    #[default]
+    Forward,
+    Backward,
 }
 
 /// Trait for anything that can be in a cycle.
@@ -122,7 +414,15 @@ where
     fn top(&self) -> Option<T>;
     fn set_top(&mut self, top: &str);
     fn advance(&mut self, amount: usize) -> Option<T>;
+    fn advance_where<F: Fn(&T) -> bool>(&mut self, amount: usize, keep: F) -> Option<T>;
+    fn retreat(&mut self, amount: usize) -> Option<T>;
+    fn retreat_where<F: Fn(&T) -> bool>(&mut self, amount: usize, keep: F) -> Option<T>;
     fn peek_next(&self) -> Option<T>;
+    fn peek_next_where<F: Fn(&T) -> bool>(&self, keep: F) -> Option<T>;
+    fn peek_previous_where<F: Fn(&T) -> bool>(&self, keep: F) -> Option<T>;
+    fn set_next(&mut self, next: &str);
+    fn set_previous(&mut self, previous: &str);
+    fn select_index(&mut self, index: usize) -> Option<T>;
     fn includes(&self, item: &T) -> bool;
     fn add(&mut self, item: &T) -> bool;
     fn delete(&mut self, item: &T) -> bool;
@@ -156,6 +456,49 @@ where
         self.first().cloned()
     }
 
+    /// Advance, then keep going until we land on an entry the predicate
+    /// accepts. If no entry is acceptable, the cycle is left as it was.
+    fn advance_where<F: Fn(&T) -> bool>(&mut self, amount: usize, keep: F) -> Option<T> {
+        if self.is_empty() || amount > self.len() {
+            return None;
+        }
+        self.rotate_left(amount);
+        if let Some(idx) = self.iter().position(keep) {
+            self.rotate_left(idx);
+            self.first().cloned()
+        } else {
+            self.rotate_right(amount);
+            None
+        }
+    }
+
+    fn retreat(&mut self, amount: usize) -> Option<T> {
+        if self.is_empty() || amount > self.len() {
+            return None;
+        }
+        self.rotate_right(amount);
+        self.first().cloned()
+    }
+
+    /// Move backward, then keep going until we land on an entry the predicate
+    /// accepts. If no entry is acceptable, the cycle is left as it was.
+    fn retreat_where<F: Fn(&T) -> bool>(&mut self, amount: usize, keep: F) -> Option<T> {
+        if self.is_empty() || amount > self.len() {
+            return None;
+        }
+        self.rotate_right(amount);
+        let found = std::iter::once(0)
+            .chain((1..self.len()).rev())
+            .find(|idx| self.get(*idx).is_some_and(&keep));
+        if let Some(idx) = found {
+            self.rotate_left(idx);
+            self.first().cloned()
+        } else {
+            self.rotate_left(amount);
+            None
+        }
+    }
+
     fn peek_next(&self) -> Option<T> {
         if self.len() == 1 {
             self.first().cloned()
@@ -164,6 +507,57 @@ where
         }
     }
 
+    /// Peek at what `advance_where(1, keep)` would land on.
+    fn peek_next_where<F: Fn(&T) -> bool>(&self, keep: F) -> Option<T> {
+        (1..self.len())
+            .chain(std::iter::once(0))
+            .filter_map(|idx| self.get(idx))
+            .find(|xs| keep(xs))
+            .cloned()
+    }
+
+    /// Peek at what `retreat_where(1, keep)` would land on.
+    fn peek_previous_where<F: Fn(&T) -> bool>(&self, keep: F) -> Option<T> {
+        (1..self.len())
+            .rev()
+            .chain(std::iter::once(0))
+            .filter_map(|idx| self.get(idx))
+            .find(|xs| keep(xs))
+            .cloned()
+    }
+
+    /// Arrange the cycle so the given entry comes up on the next advance.
+    fn set_next(&mut self, next: &str) {
+        if self.len() < 2 {
+            return;
+        }
+        if let Some(idx) = self.iter().position(|xs| xs.identifier() == *next) {
+            self.rotate_left(idx);
+            self.rotate_right(1);
+        }
+    }
+
+    /// Arrange the cycle so the given entry comes up on the next retreat.
+    fn set_previous(&mut self, previous: &str) {
+        if self.len() < 2 {
+            return;
+        }
+        if let Some(idx) = self.iter().position(|xs| xs.identifier() == *previous) {
+            self.rotate_left(idx);
+            self.rotate_left(1);
+        }
+    }
+
+    /// Jump straight to the entry at the given index, counting from the
+    /// current top. Out-of-range indexes leave the cycle alone.
+    fn select_index(&mut self, index: usize) -> Option<T> {
+        if index >= self.len() {
+            return None;
+        }
+        self.rotate_left(index);
+        self.first().cloned()
+    }
+
     fn includes(&self, item: &T) -> bool {
         self.iter().any(|xs| xs == item)
     }
@@ -369,6 +763,16 @@ mod tests {
         assert!(testcycle.delete(&"two"));
         assert!(!testcycle.includes(&"two"));
 
+        testcycle.set_next("three");
+        assert_eq!(
+            testcycle.peek_next().expect("next should be three"),
+            "three"
+        );
+        assert_eq!(
+            testcycle.advance(1).expect("advancing should work"),
+            "three"
+        );
+
         assert!(testcycle.add(&"four"));
         assert!(!testcycle.add(&"four"));
         assert!(testcycle.add(&"five"));
@@ -378,6 +782,198 @@ mod tests {
         assert_eq!(testcycle.len(), 3);
     }
 
+    #[test]
+    fn finding_missing_items() {
+        let mut set = EquipSet::new(
+            1,
+            "Scout".to_string(),
+            vec!["helm".to_string(), "gone-boots".to_string()],
+            vec![],
+            "armor_light".to_string(),
+        );
+        set.set_part(SetPart::Right, Some("gone-bow".to_string()));
+        set.set_part(SetPart::Ammo, Some("arrows".to_string()));
+
+        let missing = set.missing(|spec| !spec.starts_with("gone-"));
+        assert_eq!(
+            missing,
+            vec![
+                (SetPart::Armor, "gone-boots".to_string()),
+                (SetPart::Right, "gone-bow".to_string())
+            ]
+        );
+        assert!(set.missing(|_| true).is_empty());
+    }
+
+    #[test]
+    fn diffing_equipsets() {
+        let mut set = EquipSet::new(
+            1,
+            "Scout".to_string(),
+            vec![
+                "helm".to_string(),
+                "cuirass".to_string(),
+                "boots".to_string(),
+            ],
+            vec![33, 34],
+            "armor_light".to_string(),
+        );
+        set.set_part(SetPart::Right, Some("bow".to_string()));
+        set.set_part(SetPart::Ammo, Some("arrows".to_string()));
+
+        let current = CurrentGear {
+            items: vec!["helm".to_string(), "robes".to_string(), "boots".to_string()],
+            empty_slots: vec![33],
+            ammo: "arrows".to_string(),
+            right: "staff".to_string(),
+            ..Default::default()
+        };
+        let diff = set.diff(&current, true);
+        assert_eq!(diff.equip, vec!["cuirass"]);
+        assert_eq!(diff.unequip, vec![34]);
+        assert_eq!(diff.parts, vec![SetPart::Right]);
+        assert!(!diff.is_empty());
+
+        let diff = set.diff(&current, false);
+        assert!(diff.unequip.is_empty());
+
+        let wearing = CurrentGear {
+            items: set.items.clone(),
+            empty_slots: vec![33, 34, 35],
+            ammo: "arrows".to_string(),
+            right: "bow".to_string(),
+            // Sets without a power leave it alone.
+            power: "shout".to_string(),
+            ..Default::default()
+        };
+        assert!(set.diff(&wearing, true).is_empty());
+    }
+
+    #[test]
+    fn inheriting_equipsets() {
+        // Slot masks: helmets cover the head, cuirasses the body.
+        let slots = |spec: &str| match spec {
+            "iron-helm" | "steel-helm" | "hood" => 1 << 0,
+            "iron-cuirass" => 1 << 2,
+            _ => 0,
+        };
+        let mut base = EquipSet::new(
+            0,
+            "Iron".to_string(),
+            vec!["iron-helm".to_string(), "iron-cuirass".to_string()],
+            vec![5],
+            "armor_heavy".to_string(),
+        );
+        base.set_part(SetPart::Right, Some("iron-sword".to_string()));
+
+        let mut steel = EquipSet::new(
+            1,
+            "Iron, steel helm".to_string(),
+            vec!["steel-helm".to_string()],
+            Vec::new(),
+            "armor_heavy".to_string(),
+        );
+        steel.parent = Some(0);
+        let mut bare = EquipSet::new(
+            2,
+            "Iron, no helm".to_string(),
+            Vec::new(),
+            vec![0],
+            "armor_heavy".to_string(),
+        );
+        bare.parent = Some(1);
+        bare.set_part(SetPart::Right, Some("mace".to_string()));
+        let sets = vec![base.clone(), steel.clone(), bare];
+
+        let resolved = resolve_equipset(&sets, 1, &slots).expect("the set should resolve");
+        assert_eq!(resolved.name(), "Iron, steel helm");
+        assert_eq!(resolved.items, vec!["iron-cuirass", "steel-helm"]);
+        assert_eq!(
+            resolved.part(SetPart::Right).map(|xs| xs.as_str()),
+            Some("iron-sword")
+        );
+
+        // Grandchildren see through both parents.
+        let resolved = resolve_equipset(&sets, 2, &slots).expect("the set should resolve");
+        assert_eq!(resolved.items, vec!["iron-cuirass"]);
+        assert_eq!(resolved.empty, vec![5, 0]);
+        assert_eq!(
+            resolved.part(SetPart::Right).map(|xs| xs.as_str()),
+            Some("mace")
+        );
+
+        // Loops stop instead of spinning forever.
+        let mut looped = sets.clone();
+        looped[0].parent = Some(2);
+        assert!(resolve_equipset(&looped, 2, &slots).is_some());
+
+        let mut captured = base.clone();
+        captured.items = vec!["hood".to_string(), "iron-cuirass".to_string()];
+        captured.trim_to_overrides(&base);
+        assert_eq!(captured.items, vec!["hood"]);
+        assert!(captured.empty.is_empty());
+        assert_eq!(captured.parts(), vec![SetPart::Armor]);
+    }
+
+    #[test]
+    fn advancing_with_a_predicate() {
+        let mut testcycle: Vec<String> = ["one", "two", "three", "four"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+        let not_two = |xs: &String| xs != "two";
+
+        let peeked = testcycle
+            .peek_next_where(not_two)
+            .expect("peeking should find an entry");
+        assert_eq!(peeked, "three");
+        let advanced = testcycle
+            .advance_where(1, not_two)
+            .expect("advancing should find an entry");
+        assert_eq!(advanced, peeked);
+        assert_eq!(
+            testcycle.advance_where(1, not_two),
+            Some("four".to_string())
+        );
+        assert_eq!(testcycle.advance_where(1, not_two), Some("one".to_string()));
+
+        // If nothing matches, nothing moves.
+        assert_eq!(testcycle.advance_where(1, |_| false), None);
+        assert_eq!(testcycle.top(), Some("one".to_string()));
+        assert_eq!(testcycle.peek_next_where(|_| false), None);
+    }
+
+    #[test]
+    fn moving_backward_and_selecting() {
+        let mut testcycle: Vec<String> = ["one", "two", "three", "four"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+        let not_four = |xs: &String| xs != "four";
+
+        assert_eq!(testcycle.retreat(1), Some("four".to_string()));
+        assert_eq!(testcycle.retreat(1), Some("three".to_string()));
+        assert_eq!(testcycle.advance(2), Some("one".to_string()));
+
+        assert_eq!(
+            testcycle.peek_previous_where(not_four),
+            Some("three".to_string())
+        );
+        assert_eq!(
+            testcycle.retreat_where(1, not_four),
+            Some("three".to_string())
+        );
+        assert_eq!(testcycle.retreat_where(1, |_| false), None);
+        assert_eq!(testcycle.top(), Some("three".to_string()));
+
+        testcycle.set_previous("one");
+        assert_eq!(testcycle.retreat(1), Some("one".to_string()));
+
+        assert_eq!(testcycle.select_index(2), Some("three".to_string()));
+        assert_eq!(testcycle.select_index(4), None);
+        assert_eq!(testcycle.top(), Some("three".to_string()));
+    }
+
     #[test]
     fn hud_item_cycles() {
         use crate::data::item_cache::ItemCache;
@@ -1,12 +1,20 @@
 //! Management of the cycle data: serialization and mutation.
 
+use std::cmp::Ordering;
+use std::collections::HashMap;
 use std::fmt::Display;
 
 use cxx::CxxVector;
+use strum::{Display, EnumString};
 
 use super::control::MenuEventResponse;
 use super::cycleentries::*;
+use super::filters::AdvanceFilter;
 use super::keys::CycleSlot;
+use super::loadout::{Loadout, LoadoutSet};
+use super::macros::MacroStep;
+use super::settings::OverflowPolicy;
+use super::triggers::Trigger;
 use super::user_settings;
 use crate::data::item_cache::ItemCache;
 use crate::data::{BaseType, HudItem};
@@ -32,9 +40,30 @@ pub struct CycleData {
     utility: Vec<String>,
     /// Equipment sets.
     equipsets: Vec<EquipSet>,
+    /// Named snapshots of the four item cycles that the player can swap between.
+    profiles: Vec<CycleProfile>,
+    /// The name of the profile the live cycles belong to. Empty if none.
+    active_profile: String,
+    /// Pinned and skip-when-unusable flags, keyed by cycle and then form spec.
+    entry_flags: HashMap<CycleSlot, HashMap<String, EntryFlags>>,
+    /// How often and how lately the player has equipped or used items, keyed by form spec.
+    usage: HashMap<String, UsageStats>,
+    /// Ticks once per recorded use or added entry so we can order these
+    /// events without asking the game the time.
+    usage_clock: u64,
+    /// The usage clock reading when each entry joined its cycle, keyed by
+    /// cycle and then form spec. Entries from older saves have no reading.
+    added: HashMap<CycleSlot, HashMap<String, u64>>,
+    /// Sort modes the player wants kept applied to a cycle as it changes.
+    sort_modes: HashMap<CycleSlot, SortMode>,
+    /// Quickslot bindings the player made in game, as (key code, target) pairs.
+    quickslots: Vec<(u32, String)>,
+    /// Recorded macros, keyed by the macro key they were saved on.
+    macros: Vec<(u32, Vec<MacroStep>)>,
     /// Was the hud visible when we saved?
     pub hud_visible: bool,
-    /// Was this cycle loaded from a cosave or are we operating on defaults?
+    /// Was this cycle loaded from a cosave or started from a template, or are we
+    /// operating on defaults?
     pub loaded: bool,
 }
 
@@ -46,12 +75,77 @@ impl Default for CycleData {
             power: Default::default(),
             utility: Default::default(),
             equipsets: Default::default(),
+            profiles: Default::default(),
+            active_profile: Default::default(),
+            entry_flags: Default::default(),
+            usage: Default::default(),
+            usage_clock: 0,
+            added: Default::default(),
+            sort_modes: Default::default(),
+            quickslots: Vec::new(),
+            macros: Vec::new(),
             hud_visible: true,
             loaded: false,
         }
     }
 }
 
+/// A named set of item cycles, e.g., "melee" or "mage", along with the
+/// per-entry flags and sort modes that go with them. Equipment sets are
+/// shared by all profiles, so they are not part of this struct.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CycleProfile {
+    /// The player-chosen name for this profile. Unique.
+    pub name: String,
+    pub power: Vec<String>,
+    pub utility: Vec<String>,
+    pub left: Vec<String>,
+    pub right: Vec<String>,
+    pub entry_flags: HashMap<CycleSlot, HashMap<String, EntryFlags>>,
+    pub added: HashMap<CycleSlot, HashMap<String, u64>>,
+    pub sort_modes: HashMap<CycleSlot, SortMode>,
+}
+
+/// The parts of the cycle data the player edits directly, copied so that an
+/// edit can be undone. Usage stats and HUD visibility are not edits. Profiles
+/// are included so undoing past a profile switch puts the old one back.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CycleSnapshot {
+    power: Vec<String>,
+    utility: Vec<String>,
+    left: Vec<String>,
+    right: Vec<String>,
+    equipsets: Vec<EquipSet>,
+    entry_flags: HashMap<CycleSlot, HashMap<String, EntryFlags>>,
+    added: HashMap<CycleSlot, HashMap<String, u64>>,
+    sort_modes: HashMap<CycleSlot, SortMode>,
+    profiles: Vec<CycleProfile>,
+    active_profile: String,
+}
+
+/// Orders the player can sort a cycle into.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, Display, EnumString)]
+#[strum(ascii_case_insensitive)]
+pub enum SortMode {
+    /// Alphabetical by item name.
+    Name,
+    /// Grouped by the kind of item, then alphabetical.
+    Category,
+    /// Most recently equipped or used first.
+    Recent,
+    /// Most often equipped or used first.
+    Frequent,
+}
+
+/// How often and how lately the player has equipped or used an item.
+#[derive(Debug, Clone, Default, Hash, PartialEq, Eq)]
+pub struct UsageStats {
+    /// How many times the item has been equipped or used.
+    pub count: u32,
+    /// The usage clock reading at the most recent use. Zero if never used.
+    pub last_used: u64,
+}
+
 impl CycleData {
     /// Clear all cycles, including the equipsets.
     pub fn clear(&mut self) {
@@ -104,9 +198,63 @@ impl CycleData {
         self.get_cycle_mut(which).advance(amount)
     }
 
-    /// Advance the given cycle, skipping over the passed-in item if necessary.
-    pub fn advance_skipping(&mut self, which: &CycleSlot, skip: HudItem) -> Option<String> {
-        self.get_cycle_mut(which).advance_skipping(&skip)
+    /// Move the given cycle by one in the given direction, passing over any entries
+    /// flagged to be skipped when the player can't use them and any entries the
+    /// filter rules reject. Returns the newly-top item.
+    pub fn advance_usable(
+        &mut self,
+        which: &CycleSlot,
+        direction: Direction,
+        filter: &AdvanceFilter,
+        cache: &mut ItemCache,
+    ) -> Option<String> {
+        let unusable = self.unusable_entries(which, filter, cache);
+        let keep = |xs: &String| !unusable.contains(xs);
+        match direction {
+            Direction::Forward => self.get_cycle_mut(which).advance_where(1, keep),
+            Direction::Backward => self.get_cycle_mut(which).retreat_where(1, keep),
+        }
+    }
+
+    /// Peek at the item `advance_usable()` would land on.
+    pub fn peek_next_usable(
+        &self,
+        which: &CycleSlot,
+        direction: Direction,
+        filter: &AdvanceFilter,
+        cache: &mut ItemCache,
+    ) -> Option<String> {
+        let unusable = self.unusable_entries(which, filter, cache);
+        let keep = |xs: &String| !unusable.contains(xs);
+        match direction {
+            Direction::Forward => self.get_cycle(which).peek_next_where(keep),
+            Direction::Backward => self.get_cycle(which).peek_previous_where(keep),
+        }
+    }
+
+    /// Move the given cycle, skipping over the passed-in item as well as
+    /// anything `advance_usable()` would skip.
+    pub fn advance_skipping(
+        &mut self,
+        which: &CycleSlot,
+        skip: HudItem,
+        direction: Direction,
+        filter: &AdvanceFilter,
+        cache: &mut ItemCache,
+    ) -> Option<String> {
+        let unusable = self.unusable_entries(which, filter, cache);
+        let skip = skip.form_string();
+        let keep = |xs: &String| *xs != skip && !unusable.contains(xs);
+        match direction {
+            Direction::Forward => self.get_cycle_mut(which).advance_where(1, keep),
+            Direction::Backward => self.get_cycle_mut(which).retreat_where(1, keep),
+        }
+    }
+
+    /// Make the entry at the given index the current one. The index counts
+    /// from the current top, which is the order Papyrus sees cycles in.
+    pub fn select_index(&mut self, which: &CycleSlot, index: usize) -> Option<String> {
+        self.get_cycle_mut(which).select_index(index)
     }
 
     /// Advance the right-hand cycle skipping over all two-handed items to the next one-hander.
@@ -149,45 +297,96 @@ impl CycleData {
     /// Does not change the current item in the cycle, unless the current item is
     /// the one removed. Adds at the end.
     pub fn toggle(&mut self, which: &CycleSlot, item: HudItem) -> MenuEventResponse {
-        let cycle = match which {
-            CycleSlot::Power => {
-                if !matches!(item.kind(), BaseType::Power(_) | BaseType::Shout(_)) {
-                    return MenuEventResponse::ItemInappropriate;
-                }
-                &mut self.power
-            }
-            CycleSlot::Left => {
-                if !item.kind().left_hand_ok() {
-                    return MenuEventResponse::ItemInappropriate;
-                }
-                &mut self.left
-            }
-            CycleSlot::Right => {
-                if !item.kind().right_hand_ok() {
-                    return MenuEventResponse::ItemInappropriate;
-                }
-                &mut self.right
-            }
-            CycleSlot::Utility => {
-                if !item.kind().is_utility() {
-                    return MenuEventResponse::ItemInappropriate;
-                }
-                &mut self.utility
-            }
-        };
-
-        // We have at most 20 items, so we do this blithely.
-        let settings = user_settings();
+        if !CycleData::accepts(which, item.kind()) {
+            return MenuEventResponse::ItemInappropriate;
+        }
         let spec = item.form_string();
+        let cycle = self.get_cycle_mut(which);
+
+        // Cycles are short, so we do this blithely.
         if cycle.includes(&spec) {
             cycle.delete(&spec);
             MenuEventResponse::ItemRemoved
-        } else if cycle.len() >= settings.maxlen() as usize {
-            return MenuEventResponse::TooManyItems;
         } else {
-            cycle.add(&spec);
+            let settings = user_settings();
+            self.add_with_overflow(
+                which,
+                spec.as_str(),
+                settings.maxlen() as usize,
+                settings.overflow_policy(),
+            )
+        }
+    }
+
+    /// Add an entry to a cycle, making room first if the cycle is full and the
+    /// overflow policy allows it. The current and pinned entries are never
+    /// evicted. Reports the form specs of any evicted entries.
+    pub fn add_with_overflow(
+        &mut self,
+        which: &CycleSlot,
+        form_spec: &str,
+        maxlen: usize,
+        policy: OverflowPolicy,
+    ) -> MenuEventResponse {
+        let needed = (self.get_cycle(which).len() + 1).saturating_sub(maxlen);
+        let candidates = self.eviction_order(which, policy);
+        if candidates.len() < needed {
+            return MenuEventResponse::TooManyItems;
+        }
+
+        let evicted: Vec<String> = candidates.into_iter().take(needed).collect();
+        for victim in evicted.iter() {
+            log::info!("evicting an entry from the {which} cycle to make room; spec={victim};");
+            self.get_cycle_mut(which).delete(victim);
+        }
+        self.push_entry(which, form_spec);
+
+        if evicted.is_empty() {
             MenuEventResponse::ItemAdded
+        } else {
+            MenuEventResponse::ItemAddedWithEvictions(evicted)
+        }
+    }
+
+    /// The entries a full cycle may give up under the given policy, first to go first.
+    fn eviction_order(&self, which: &CycleSlot, policy: OverflowPolicy) -> Vec<String> {
+        let top = self.get_top(which);
+        let pinned = self.pinned(which);
+        let added = |spec: &String| {
+            self.added
+                .get(which)
+                .and_then(|stamps| stamps.get(spec))
+                .copied()
+                .unwrap_or_default()
+        };
+        let mut candidates: Vec<String> = self
+            .get_cycle(which)
+            .iter()
+            .filter(|xs| Some(*xs) != top.as_ref() && Some(*xs) != pinned.as_ref())
+            .cloned()
+            .collect();
+        // Stable sorts, so ties go to whichever comes first after the current entry.
+        match policy {
+            OverflowPolicy::Reject => candidates.clear(),
+            OverflowPolicy::EvictOldest => candidates.sort_by_key(added),
+            OverflowPolicy::EvictLeastUsed => {
+                candidates.sort_by_key(|xs| (self.usage(xs).count, added(xs)))
+            }
         }
+        candidates
+    }
+
+    /// Add an entry to a cycle and note when it joined.
+    fn push_entry(&mut self, which: &CycleSlot, form_spec: &str) -> bool {
+        if !self.get_cycle_mut(which).add(&form_spec.to_string()) {
+            return false;
+        }
+        self.usage_clock += 1;
+        self.added
+            .entry(which.clone())
+            .or_default()
+            .insert(form_spec.to_string(), self.usage_clock);
+        true
     }
 
     pub fn remove_zero_count_items(&mut self, form_spec: &str, kind: &BaseType) {
@@ -208,9 +407,47 @@ impl CycleData {
         self.get_cycle(which).includes(&item.form_string())
     }
 
+    /// Can items of this kind go into the given cycle?
+    pub fn accepts(which: &CycleSlot, kind: &BaseType) -> bool {
+        match which {
+            CycleSlot::Power => matches!(kind, BaseType::Power(_) | BaseType::Shout(_)),
+            CycleSlot::Left => kind.left_hand_ok(),
+            CycleSlot::Right => kind.right_hand_ok(),
+            CycleSlot::Utility => kind.is_utility(),
+        }
+    }
+
+    /// Add items to a cycle in order, emptying it first if asked. Items that
+    /// don't belong in the cycle are left out. A full cycle makes room the way
+    /// the overflow policy says, as `toggle()` does. Returns the number of
+    /// items added.
+    pub fn fill(&mut self, which: &CycleSlot, items: &[HudItem], replace: bool) -> usize {
+        let settings = user_settings();
+        let maxlen = settings.maxlen() as usize;
+        let policy = settings.overflow_policy();
+        if replace {
+            self.get_cycle_mut(which).clear();
+        }
+        let mut added = 0;
+        for item in items.iter() {
+            let spec = item.form_string();
+            if !CycleData::accepts(which, item.kind()) || self.get_cycle(which).includes(&spec) {
+                continue;
+            }
+            match self.add_with_overflow(which, spec.as_str(), maxlen, policy) {
+                MenuEventResponse::TooManyItems => {
+                    log::debug!("{which} cycle is full; not adding '{}'", item.name());
+                    break;
+                }
+                _ => added += 1,
+            }
+        }
+        added
+    }
+
     /// Make sure the given cycle includes this item, adding it if it does not.
     pub fn add_item(&mut self, which: CycleSlot, item: &HudItem) -> bool {
-        self.get_cycle_mut(&which).add(&item.form_string())
+        self.push_entry(&which, item.form_string().as_str())
     }
 
     pub fn remove_item(&mut self, which: CycleSlot, item: &HudItem) -> bool {
@@ -240,6 +477,419 @@ impl CycleData {
         self.hud_visible
     }
 
+    // ---------- per-entry flags
+
+    /// Get the flags set for an entry in the given cycle.
+    pub fn entry_flags(&self, which: &CycleSlot, form_spec: &str) -> EntryFlags {
+        self.entry_flags
+            .get(which)
+            .and_then(|flags| flags.get(form_spec))
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Pin or unpin an entry in the given cycle. A cycle has at most one
+    /// pinned entry, so pinning an entry unpins the previous one.
+    pub fn set_pinned(&mut self, which: &CycleSlot, form_spec: &str, pinned: bool) -> bool {
+        if !self.get_cycle(which).includes(&form_spec.to_string()) {
+            return false;
+        }
+        if pinned {
+            if let Some(previous) = self.pinned(which) {
+                self.update_flags(which, previous.as_str(), |xs| xs.pinned = false);
+            }
+        }
+        self.update_flags(which, form_spec, |xs| xs.pinned = pinned);
+        true
+    }
+
+    /// Set whether an entry should be passed over when the player can't use it.
+    pub fn set_skip_unusable(&mut self, which: &CycleSlot, form_spec: &str, skip: bool) -> bool {
+        if !self.get_cycle(which).includes(&form_spec.to_string()) {
+            return false;
+        }
+        self.update_flags(which, form_spec, |xs| xs.skip_unusable = skip);
+        true
+    }
+
+    /// Get the form spec of the pinned entry for this cycle, if it has one.
+    pub fn pinned(&self, which: &CycleSlot) -> Option<String> {
+        let flags = self.entry_flags.get(which)?;
+        self.get_cycle(which)
+            .iter()
+            .find(|xs| flags.get(*xs).is_some_and(|flag| flag.pinned))
+            .cloned()
+    }
+
+    /// Get the form specs of every entry in this cycle flagged to be skipped when unusable.
+    pub fn skip_unusable_entries(&self, which: &CycleSlot) -> Vec<String> {
+        self.get_cycle(which)
+            .iter()
+            .filter(|xs| self.entry_flags(which, xs).skip_unusable)
+            .cloned()
+            .collect()
+    }
+
+    /// Line up the given cycle so its next move in the given direction lands on
+    /// the pinned entry. Does nothing if the cycle has no pin or the pinned entry
+    /// is already on top.
+    pub fn return_to_pinned(&mut self, which: &CycleSlot, direction: Direction) {
+        let Some(pinned) = self.pinned(which) else {
+            return;
+        };
+        if self.get_top(which).as_ref() != Some(&pinned) {
+            log::debug!("returning {which} cycle to its pinned entry; spec={pinned};");
+            match direction {
+                Direction::Forward => self.get_cycle_mut(which).set_next(pinned.as_str()),
+                Direction::Backward => self.get_cycle_mut(which).set_previous(pinned.as_str()),
+            }
+        }
+    }
+
+    fn update_flags(
+        &mut self,
+        which: &CycleSlot,
+        form_spec: &str,
+        change: impl FnOnce(&mut EntryFlags),
+    ) {
+        let flags = self.entry_flags.entry(which.clone()).or_default();
+        let entry = flags.entry(form_spec.to_string()).or_default();
+        change(entry);
+        if entry.is_empty() {
+            flags.remove(form_spec);
+        }
+    }
+
+    /// Entries in this cycle the player can't use now: those flagged as
+    /// skip-when-unusable with nothing left, plus whatever the filter rejects.
+    fn unusable_entries(
+        &self,
+        which: &CycleSlot,
+        filter: &AdvanceFilter,
+        cache: &mut ItemCache,
+    ) -> Vec<String> {
+        self.get_cycle(which)
+            .iter()
+            .filter(|xs| {
+                let item = cache.get(xs);
+                let flagged = self.entry_flags(which, xs).skip_unusable
+                    && item.count_matters()
+                    && item.count() == 0;
+                flagged || filter.skip(&item)
+            })
+            .cloned()
+            .collect()
+    }
+
+    // ---------- usage tracking and sorting
+
+    /// Note that the player just equipped or used this item.
+    pub fn record_use(&mut self, form_spec: &str) {
+        self.usage_clock += 1;
+        let stats = self.usage.entry(form_spec.to_string()).or_default();
+        stats.count = stats.count.saturating_add(1);
+        stats.last_used = self.usage_clock;
+    }
+
+    /// Get usage stats for an item. Items never used have all-zero stats.
+    pub fn usage(&self, form_spec: &str) -> UsageStats {
+        self.usage.get(form_spec).cloned().unwrap_or_default()
+    }
+
+    /// Sort the given cycle once. The current item stays current, so the
+    /// sorted order starts from it and wraps around.
+    pub fn sort_cycle(&mut self, which: &CycleSlot, mode: SortMode, cache: &mut ItemCache) {
+        let top = self.get_top(which);
+        let mut keyed: Vec<(String, HudItem)> = self
+            .get_cycle(which)
+            .iter()
+            .map(|spec| (spec.clone(), cache.get(spec)))
+            .collect();
+        keyed.sort_by(|a, b| self.compare_for_sort(mode, a, b));
+
+        let mut sorted: Vec<String> = keyed.into_iter().map(|(spec, _)| spec).collect();
+        if let Some(idx) = top.and_then(|top| sorted.iter().position(|xs| *xs == top)) {
+            sorted.rotate_left(idx);
+        }
+        *self.get_cycle_mut(which) = sorted;
+    }
+
+    /// Keep the given cycle sorted by this mode from now on, or stop keeping
+    /// it sorted if the mode is None. A new mode is applied right away.
+    pub fn set_sort_mode(
+        &mut self,
+        which: &CycleSlot,
+        mode: Option<SortMode>,
+        cache: &mut ItemCache,
+    ) {
+        match mode {
+            Some(mode) => {
+                self.sort_modes.insert(which.clone(), mode);
+                self.sort_cycle(which, mode, cache);
+            }
+            None => {
+                self.sort_modes.remove(which);
+            }
+        }
+    }
+
+    /// The sort mode kept applied to this cycle, if any.
+    pub fn sort_mode(&self, which: &CycleSlot) -> Option<SortMode> {
+        self.sort_modes.get(which).copied()
+    }
+
+    /// Re-sort every cycle that has a sort mode kept applied. Call this after
+    /// anything that adds to a cycle or changes usage.
+    pub fn resort(&mut self, cache: &mut ItemCache) {
+        let modes: Vec<(CycleSlot, SortMode)> = self
+            .sort_modes
+            .iter()
+            .map(|(slot, mode)| (slot.clone(), *mode))
+            .collect();
+        for (slot, mode) in modes {
+            self.sort_cycle(&slot, mode, cache);
+        }
+    }
+
+    /// Ties in every mode fall back to the item name so the order is stable.
+    fn compare_for_sort(
+        &self,
+        mode: SortMode,
+        a: &(String, HudItem),
+        b: &(String, HudItem),
+    ) -> Ordering {
+        let by_name = || a.1.name().to_lowercase().cmp(&b.1.name().to_lowercase());
+        match mode {
+            SortMode::Name => by_name(),
+            SortMode::Category => category_rank(a.1.kind())
+                .cmp(&category_rank(b.1.kind()))
+                .then_with(by_name),
+            SortMode::Recent => self
+                .usage(&b.0)
+                .last_used
+                .cmp(&self.usage(&a.0).last_used)
+                .then_with(by_name),
+            SortMode::Frequent => self
+                .usage(&b.0)
+                .count
+                .cmp(&self.usage(&a.0).count)
+                .then_with(by_name),
+        }
+    }
+
+    // ---------- cycle profiles
+
+    /// Get the names of all saved cycle profiles, in creation order.
+    pub fn profile_names(&self) -> Vec<String> {
+        self.profiles.iter().map(|xs| xs.name.clone()).collect()
+    }
+
+    /// The name of the profile the live cycles belong to, if any.
+    pub fn active_profile(&self) -> String {
+        self.active_profile.clone()
+    }
+
+    /// Snapshot the live cycles into a new profile with the given name and
+    /// make it the active profile. Fails if the name is empty or taken.
+    pub fn create_profile(&mut self, name: String) -> bool {
+        let name = name.trim().to_string();
+        if name.is_empty() || self.profiles.iter().any(|xs| xs.name == name) {
+            return false;
+        }
+        log::info!("Saving the current cycles as profile '{name}'.");
+        let profile = self.snapshot_profile(name.clone());
+        self.profiles.push(profile);
+        self.active_profile = name;
+        true
+    }
+
+    /// Swap the live cycles for the ones stored in the named profile, with
+    /// their entry flags and sort modes. The live cycles are first saved back
+    /// into the active profile, if there is one. The caller is responsible for
+    /// validating the result against the game.
+    pub fn switch_profile(&mut self, name: &str) -> bool {
+        let Some(target) = self.profiles.iter().find(|xs| xs.name == name).cloned() else {
+            log::info!("No cycle profile named '{name}' exists.");
+            return false;
+        };
+
+        let active = self.active_profile.clone();
+        if let Some(idx) = self.profiles.iter().position(|xs| xs.name == active) {
+            self.profiles[idx] = self.snapshot_profile(active);
+        }
+
+        log::info!("Switching to cycle profile '{name}'.");
+        self.power = target.power;
+        self.utility = target.utility;
+        self.left = target.left;
+        self.right = target.right;
+        self.entry_flags = target.entry_flags;
+        self.added = target.added;
+        self.sort_modes = target.sort_modes;
+        self.active_profile = target.name;
+        true
+    }
+
+    /// Delete the named profile. The live cycles are not touched, even if
+    /// the deleted profile was the active one.
+    pub fn remove_profile(&mut self, name: &str) -> bool {
+        let orig_len = self.profiles.len();
+        self.profiles.retain(|xs| xs.name != name);
+        if self.active_profile == name {
+            self.active_profile.clear();
+        }
+        orig_len != self.profiles.len()
+    }
+
+    fn snapshot_profile(&self, name: String) -> CycleProfile {
+        CycleProfile {
+            name,
+            power: self.power.clone(),
+            utility: self.utility.clone(),
+            left: self.left.clone(),
+            right: self.right.clone(),
+            entry_flags: self.entry_flags.clone(),
+            added: self.added.clone(),
+            sort_modes: self.sort_modes.clone(),
+        }
+    }
+
+    // ---------- undo support
+
+    /// Copy everything an edit might change.
+    pub fn snapshot(&self) -> CycleSnapshot {
+        CycleSnapshot {
+            power: self.power.clone(),
+            utility: self.utility.clone(),
+            left: self.left.clone(),
+            right: self.right.clone(),
+            equipsets: self.equipsets.clone(),
+            entry_flags: self.entry_flags.clone(),
+            added: self.added.clone(),
+            sort_modes: self.sort_modes.clone(),
+            profiles: self.profiles.clone(),
+            active_profile: self.active_profile.clone(),
+        }
+    }
+
+    /// Put back the state from a snapshot.
+    pub fn restore(&mut self, snapshot: CycleSnapshot) {
+        self.power = snapshot.power;
+        self.utility = snapshot.utility;
+        self.left = snapshot.left;
+        self.right = snapshot.right;
+        self.equipsets = snapshot.equipsets;
+        self.entry_flags = snapshot.entry_flags;
+        self.added = snapshot.added;
+        self.sort_modes = snapshot.sort_modes;
+        self.profiles = snapshot.profiles;
+        self.active_profile = snapshot.active_profile;
+    }
+
+    // ---------- loadouts
+
+    /// Copy the cycles and equipment sets into their editable form.
+    /// Equipment sets are written in id order, which is how the MCM lists them.
+    pub fn to_loadout(&self) -> Loadout {
+        let mut sets = self.equipsets.clone();
+        sets.sort_by_key(|xs| xs.id());
+        Loadout {
+            power: self.power.clone(),
+            utility: self.utility.clone(),
+            left: self.left.clone(),
+            right: self.right.clone(),
+            equipsets: sets
+                .iter()
+                .map(|set| {
+                    let mut exported = LoadoutSet::from(set);
+                    exported.parent = set
+                        .parent
+                        .and_then(|id| self.equipsets.get_by_id(id))
+                        .map(|xs| xs.name());
+                    exported
+                })
+                .collect(),
+        }
+    }
+
+    /// Bring in a loadout. When replacing, the cycles and equipment sets are
+    /// emptied first. When merging, entries are added after the existing ones
+    /// and an equipment set with a matching name is updated in place. Entries
+    /// that don't belong in their cycle or don't fit are left out.
+    pub fn apply_loadout(&mut self, loadout: Loadout, replace: bool, cache: &mut ItemCache) {
+        if replace {
+            self.clear();
+        }
+        let maxlen = user_settings().maxlen() as usize;
+        let cycles = [
+            (CycleSlot::Power, loadout.power),
+            (CycleSlot::Utility, loadout.utility),
+            (CycleSlot::Left, loadout.left),
+            (CycleSlot::Right, loadout.right),
+        ];
+        for (slot, specs) in cycles {
+            for spec in specs {
+                let item = cache.get(&spec);
+                if !CycleData::accepts(&slot, item.kind()) {
+                    log::info!("'{}' can't go in the {slot} cycle; skipping", item.name());
+                    continue;
+                }
+                if self.get_cycle(&slot).len() >= maxlen {
+                    log::info!("{slot} cycle is full; skipping '{}'", item.name());
+                    continue;
+                }
+                self.push_entry(&slot, spec.as_str());
+            }
+        }
+
+        // Parents are linked once every set is in, since a set may come before its parent.
+        let mut parents: Vec<(String, String)> = Vec::new();
+        for set in loadout.equipsets {
+            if let Some(parent) = set.parent.clone() {
+                parents.push((set.name.clone(), parent));
+            }
+            let existing = self.equipsets.iter().find(|xs| xs.name() == set.name);
+            if let Some(id) = existing.map(|xs| xs.id()) {
+                self.equipsets
+                    .update_set(id, set.items.clone(), set.empty_slots.clone());
+                if let Ok(icon) = Icon::try_from(set.icon.as_str()) {
+                    self.equipsets.set_icon_by_id(id, icon);
+                }
+                if let Some(existing) = self.equipsets.iter_mut().find(|xs| xs.id() == id) {
+                    set.apply_to(existing);
+                }
+            } else {
+                let id = self.equipsets.find_next_id();
+                let mut equipset = EquipSet::new(
+                    id,
+                    set.name.clone(),
+                    set.items.clone(),
+                    set.empty_slots.clone(),
+                    set.icon.clone(),
+                );
+                set.apply_to(&mut equipset);
+                self.equipsets.add(&equipset);
+            }
+        }
+        for (name, parent) in parents {
+            let find = |wanted: &str| {
+                self.equipsets
+                    .iter()
+                    .find(|xs| xs.name() == wanted)
+                    .map(|xs| xs.id())
+            };
+            let linked = match (find(&name), find(&parent)) {
+                (Some(id), Some(parent_id)) => self.set_equipset_parent(id, Some(parent_id)),
+                _ => false,
+            };
+            if !linked {
+                log::info!(
+                    "Equipment set '{name}' can't be based on '{parent}'; leaving it on its own."
+                );
+            }
+        }
+    }
+
     // ---------- validation
 
     /// Remove any items that have vanished from the game or from the player's
@@ -317,6 +967,14 @@ impl CycleData {
             log::info!("{}: {}", xs.id(), xs.name());
             log::info!("    {}", names.join(", "));
             log::info!("    {} empty slots", xs.empty_slots().len());
+            xs.missing(|spec| {
+                cxx::let_cxx_string!(form_spec = spec);
+                hasItemOrSpell(&form_spec) || itemCount(&form_spec) > 0
+            })
+            .iter()
+            .for_each(|(part, spec)| {
+                log::warn!("    missing {part}: {spec}");
+            });
         });
         //log::info!("hud_visible: {}", self.hud_visible);
         log::info!("Have a nice day and remember to put on a cloak if it starts snowing.");
@@ -332,6 +990,55 @@ impl CycleData {
         self.equipsets.advance(amount)
     }
 
+    pub fn retreat_equipset(&mut self, amount: usize) -> Option<EquipSet> {
+        self.equipsets.retreat(amount)
+    }
+
+    /// Make the equipset at the given index current. Equipsets are indexed in
+    /// id order, which is how the MCM lists them.
+    pub fn select_equipset_index(&mut self, index: usize) -> Option<EquipSet> {
+        let id = self.equipset_ids().get(index)?.to_string();
+        self.equipsets.set_top(id.as_str());
+        self.equipsets.top()
+    }
+
+    /// Bring the equipset with this id to the top of its cycle.
+    pub fn select_equipset_id(&mut self, id: u32) -> Option<EquipSet> {
+        self.equipsets.get_by_id(id)?;
+        self.equipsets.set_top(id.to_string().as_str());
+        self.equipsets.top()
+    }
+
+    /// Equipsets that can equip themselves, with their triggers, in id order.
+    pub fn triggered_equipsets(&self) -> Vec<(u32, Vec<Trigger>)> {
+        let mut found: Vec<(u32, Vec<Trigger>)> = self
+            .equipsets
+            .iter()
+            .filter(|xs| xs.auto_equip && !xs.triggers.is_empty())
+            .map(|xs| (xs.id(), xs.triggers.clone()))
+            .collect();
+        found.sort_by_key(|xs| xs.0);
+        found
+    }
+
+    /// Replace the triggers for an equipset.
+    pub fn set_equipset_triggers(&mut self, id: u32, triggers: Vec<Trigger>) -> bool {
+        let Some(set) = self.equipsets.iter_mut().find(|xs| xs.id() == id) else {
+            return false;
+        };
+        set.triggers = triggers;
+        true
+    }
+
+    /// Switch an equipset's triggers on or off.
+    pub fn enable_equipset_triggers(&mut self, id: u32, enabled: bool) -> bool {
+        let Some(set) = self.equipsets.iter_mut().find(|xs| xs.id() == id) else {
+            return false;
+        };
+        set.auto_equip = enabled;
+        true
+    }
+
     pub fn add_equipset(&mut self, name: String, data: EquippedData) -> bool {
         let id = self.equipsets.find_next_id();
         let set = EquipSet::new(
@@ -356,10 +1063,26 @@ impl CycleData {
         self.equipsets.rename_by_id(id, name)
     }
 
+    /// Equipset names in id order. Sets based on another set show it, like
+    /// `Steel Helm < Iron`.
     pub fn equipset_names(&self) -> Vec<String> {
         let mut sorted = self.equipsets.clone();
         sorted.sort_by_key(|xs| xs.id());
-        sorted.iter().map(|xs| xs.name()).collect()
+        sorted
+            .iter()
+            .map(|xs| self.equipset_display_name(xs))
+            .collect()
+    }
+
+    fn equipset_display_name(&self, set: &EquipSet) -> String {
+        let parent = set
+            .parent
+            .and_then(|id| self.equipsets.get_by_id(id))
+            .map(|xs| xs.name());
+        match parent {
+            Some(parent) => format!("{} < {parent}", set.name()),
+            None => set.name(),
+        }
     }
 
     pub fn equipset_ids(&self) -> Vec<u32> {
@@ -376,26 +1099,123 @@ impl CycleData {
         self.equipsets.get_by_id(id).cloned()
     }
 
+    /// Find an equipset by its name or by the name `equipset_names()` shows for it.
     pub fn equipset_by_name(&mut self, name: String) -> u32 {
         if let Some(set) = self.equipsets.iter().find(|xs| xs.name() == name) {
             set.id()
+        } else if let Some(set) = self
+            .equipsets
+            .iter()
+            .find(|xs| self.equipset_display_name(xs) == name)
+        {
+            set.id()
         } else {
             0
         }
     }
 
+    /// Base an equipset on another, or pass None to stand it alone. This only
+    /// changes the link; the caller decides what to do with the set's items.
+    /// Refuses links to missing sets and links that would make a loop.
+    pub fn set_equipset_parent(&mut self, id: u32, parent: Option<u32>) -> bool {
+        if let Some(parent_id) = parent {
+            let mut next = Some(parent_id);
+            let mut depth = 0;
+            while let Some(current) = next {
+                if current == id || depth >= MAX_INHERITANCE_DEPTH {
+                    log::warn!(
+                        "Equipment set {parent_id} can't be the parent of {id}; that makes a loop."
+                    );
+                    return false;
+                }
+                let Some(set) = self.equipsets.get_by_id(current) else {
+                    return false;
+                };
+                next = set.parent;
+                depth += 1;
+            }
+        }
+        let Some(set) = self.equipsets.iter_mut().find(|xs| xs.id() == id) else {
+            return false;
+        };
+        set.parent = parent;
+        true
+    }
+
+    /// The ids of sets based directly on this one.
+    pub fn equipset_children(&self, id: u32) -> Vec<u32> {
+        self.equipsets
+            .iter()
+            .filter(|xs| xs.parent == Some(id))
+            .map(|xs| xs.id())
+            .collect()
+    }
+
+    /// Get an equipset with everything it inherits filled in.
+    pub fn resolve_equipset(&self, id: u32, slots: &impl Fn(&str) -> u32) -> Option<EquipSet> {
+        resolve_equipset(&self.equipsets, id, slots)
+    }
+
+    /// Replace a set's contents wholesale, e.g. with a resolved copy of itself.
+    pub fn replace_equipset(&mut self, set: EquipSet) -> bool {
+        let Some(existing) = self.equipsets.iter_mut().find(|xs| xs.id() == set.id()) else {
+            return false;
+        };
+        *existing = set;
+        true
+    }
+
     pub fn set_icon_by_id(&mut self, id: u32, icon: Icon) -> bool {
         self.equipsets.set_icon_by_id(id, icon)
     }
 
+    /// Record what an equipment set should equip for its power, ammo, or a
+    /// hand, or None to have it leave that part alone. Armor is changed with
+    /// `update_equipset()`.
+    pub fn set_equipset_part(&mut self, id: u32, part: SetPart, form_spec: Option<String>) -> bool {
+        let Some(set) = self.equipsets.iter_mut().find(|xs| xs.id() == id) else {
+            return false;
+        };
+        set.set_part(part, form_spec);
+        true
+    }
+
+    /// What the quickslot on this key is bound to in this save, if anything.
+    pub fn quickslot(&self, key: u32) -> Option<String> {
+        self.quickslots
+            .iter()
+            .find(|(bound, _)| *bound == key)
+            .map(|(_, target)| target.clone())
+    }
+
+    /// Bind a quickslot key to a target, replacing any earlier binding.
+    pub fn set_quickslot(&mut self, key: u32, target: String) {
+        self.quickslots.retain(|(bound, _)| *bound != key);
+        self.quickslots.push((key, target));
+    }
+
+    /// The steps of the macro saved on this key, if any.
+    pub fn macro_steps(&self, key: u32) -> Option<Vec<MacroStep>> {
+        self.macros
+            .iter()
+            .find(|(bound, _)| *bound == key)
+            .map(|(_, steps)| steps.clone())
+    }
+
+    /// Save a macro on a key, replacing whatever was there.
+    pub fn set_macro(&mut self, key: u32, steps: Vec<MacroStep>) {
+        self.macros.retain(|(bound, _)| *bound != key);
+        self.macros.push((key, steps));
+    }
+
     // bincode serialization to cosave
 
     pub fn serialize_version() -> u32 {
-        cosave_v2::VERSION
+        cosave_v3::VERSION
     }
 
     pub fn serialize(&self) -> Vec<u8> {
-        let value = cosave_v2::CycleSerialized::from(self);
+        let value = cosave_v3::CycleSerialized::from(self);
         let config = bincode::config::standard();
         let bytes: Vec<u8> = bincode::encode_to_vec(value, config).unwrap_or_default();
         log::info!(
@@ -412,6 +1232,7 @@ impl CycleData {
             0 => cosave_v0::deserialize(bytes),
             1 => cosave_v1::deserialize(bytes),
             2 => cosave_v2::deserialize(bytes),
+            3 => cosave_v3::deserialize(bytes),
             _ => {
                 log::warn!(
                     "Cosave data is version {version}, which this plugin version cannot decode."
@@ -426,7 +1247,7 @@ impl Display for CycleData {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
-            "\npower: [{}];\nutility: [{}];\nleft: [{}];\nright: [{}];\nequipsets: [{}]",
+            "\npower: [{}];\nutility: [{}];\nleft: [{}];\nright: [{}];\nequipsets: [{}];\nprofiles: [{}]",
             self.power.join(", "),
             self.utility.join(", "),
             self.left.join(", "),
@@ -435,13 +1256,414 @@ impl Display for CycleData {
                 .iter()
                 .map(|xs| xs.name())
                 .collect::<Vec<_>>()
-                .join(",")
+                .join(","),
+            self.profile_names().join(",")
         )
     }
 }
 
+/// Where a kind of item falls when a cycle is sorted by category.
+fn category_rank(kind: &BaseType) -> u8 {
+    match kind {
+        BaseType::Weapon(_) => 0,
+        BaseType::HandToHand => 1,
+        BaseType::Armor(_) => 2,
+        BaseType::Light(_) => 3,
+        BaseType::Spell(_) => 4,
+        BaseType::Scroll(_) => 5,
+        BaseType::Shout(_) => 6,
+        BaseType::Power(_) => 7,
+        BaseType::PotionProxy(_) => 8,
+        BaseType::Potion(_) => 9,
+        BaseType::Food(_) => 10,
+        BaseType::Ammo(_) => 11,
+        BaseType::Book => 12,
+        BaseType::Equipset(_) => 13,
+        BaseType::Empty => 14,
+    }
+}
+
 // cosave version modules.
 
+pub mod cosave_v3 {
+    use std::collections::HashMap;
+    use std::str::FromStr;
+
+    use bincode::{Decode, Encode};
+
+    use super::cosave_v2;
+    use crate::controller::cycleentries::{EntryFlags, SetPart};
+    use crate::controller::cycles::{CycleData, CycleProfile, SortMode, UsageStats};
+    use crate::controller::keys::CycleSlot;
+    use crate::controller::macros::MacroStep;
+    use crate::controller::triggers::Trigger;
+
+    pub const VERSION: u32 = 3;
+
+    pub fn deserialize(bytes: Vec<u8>) -> Option<CycleData> {
+        let config = bincode::config::standard();
+        log::debug!(
+            "reading cosave format version {VERSION}; data len={};",
+            bytes.len()
+        );
+
+        match bincode::decode_from_slice::<CycleSerialized, _>(&bytes[..], config) {
+            Ok((value, _len)) => {
+                log::info!("Cycles successfully read from cosave data version {VERSION}. Save data was {} bytes.", bytes.len());
+                Some(value.into())
+            }
+            Err(e) => {
+                log::error!("Bincode cannot decode the cosave data. len={}", bytes.len());
+                log::error!("{e:#}");
+                None
+            }
+        }
+    }
+
+    type SerializedProfile = Vec<(String, Vec<String>, Vec<String>, Vec<String>, Vec<String>)>;
+    type SerializedFlags = Vec<(String, String, bool, bool)>;
+    type SerializedSortModes = Vec<(String, String)>;
+    type SerializedAdded = Vec<(String, String, u64)>;
+
+    /// Version 3 is version 2 plus everything else the player sets up: cycle
+    /// profiles, entry flags, usage stats, sort modes, equipset parts,
+    /// triggers, and parents, quickslots, and macros. Bincode encodes a
+    /// nested struct as its fields in order, so we reuse the v2 struct as-is.
+    /// Enums are written by name, and anything we can't read back is dropped
+    /// on load, leaving that part at its default.
+    #[derive(Decode, Encode, Hash, Debug, Clone, PartialEq, Eq)]
+    pub struct CycleSerialized {
+        base: cosave_v2::CycleSerialized,
+        // Vec of tuples of (name, power, utility, left, right)
+        profiles: SerializedProfile,
+        // Vec of tuples of (profile name, entry flags, sort modes, added),
+        // each laid out like the live fields below
+        profile_entries: Vec<(
+            String,
+            SerializedFlags,
+            SerializedSortModes,
+            SerializedAdded,
+        )>,
+        active_profile: String,
+        // Vec of tuples of (cycle name, formspec, pinned, skip_unusable)
+        entry_flags: SerializedFlags,
+        // Vec of tuples of (formspec, use count, last used)
+        usage: Vec<(String, u32, u64)>,
+        // Vec of tuples of (cycle name, sort mode name)
+        sort_modes: SerializedSortModes,
+        // Vec of tuples of (cycle name, formspec, usage clock when added)
+        added: SerializedAdded,
+        // Vec of tuples of (equipset id, part name, formspec)
+        set_parts: Vec<(u32, String, String)>,
+        // Vec of tuples of (equipset id, auto-equip enabled, trigger names)
+        triggers: Vec<(u32, bool, Vec<String>)>,
+        // Vec of tuples of (equipset id, parent id)
+        parents: Vec<(u32, u32)>,
+        // Vec of tuples of (key code, quickslot target)
+        quickslots: Vec<(u32, String)>,
+        // Vec of tuples of (macro key, steps as text)
+        macros: Vec<(u32, Vec<String>)>,
+    }
+
+    const SLOTS: [CycleSlot; 4] = [
+        CycleSlot::Power,
+        CycleSlot::Utility,
+        CycleSlot::Left,
+        CycleSlot::Right,
+    ];
+
+    // We only write flags and added stamps for entries still in their
+    // cycles. The cycles are given in `SLOTS` order.
+    fn flag_rows(
+        flags: &HashMap<CycleSlot, HashMap<String, EntryFlags>>,
+        cycles: [&Vec<String>; 4],
+    ) -> SerializedFlags {
+        SLOTS
+            .iter()
+            .zip(cycles)
+            .flat_map(|(slot, specs)| {
+                specs.iter().filter_map(move |spec| {
+                    let entry = flags.get(slot)?.get(spec)?;
+                    if entry.is_empty() {
+                        None
+                    } else {
+                        Some((
+                            slot.to_string(),
+                            spec.clone(),
+                            entry.pinned,
+                            entry.skip_unusable,
+                        ))
+                    }
+                })
+            })
+            .collect()
+    }
+
+    fn sort_mode_rows(modes: &HashMap<CycleSlot, SortMode>) -> SerializedSortModes {
+        SLOTS
+            .iter()
+            .filter_map(|slot| {
+                modes
+                    .get(slot)
+                    .map(|mode| (slot.to_string(), mode.to_string()))
+            })
+            .collect()
+    }
+
+    fn added_rows(
+        added: &HashMap<CycleSlot, HashMap<String, u64>>,
+        cycles: [&Vec<String>; 4],
+    ) -> SerializedAdded {
+        SLOTS
+            .iter()
+            .zip(cycles)
+            .flat_map(|(slot, specs)| {
+                specs.iter().filter_map(move |spec| {
+                    let stamp = added.get(slot)?.get(spec)?;
+                    Some((slot.to_string(), spec.clone(), *stamp))
+                })
+            })
+            .collect()
+    }
+
+    fn read_flags(rows: SerializedFlags) -> HashMap<CycleSlot, HashMap<String, EntryFlags>> {
+        let mut flags: HashMap<CycleSlot, HashMap<String, EntryFlags>> = HashMap::new();
+        rows.into_iter().for_each(|xs| {
+            let Ok(slot) = xs.0.parse::<CycleSlot>() else {
+                return;
+            };
+            let entry = EntryFlags {
+                pinned: xs.2,
+                skip_unusable: xs.3,
+            };
+            flags.entry(slot).or_default().insert(xs.1, entry);
+        });
+        flags
+    }
+
+    fn read_sort_modes(rows: SerializedSortModes) -> HashMap<CycleSlot, SortMode> {
+        rows.into_iter()
+            .filter_map(|xs| {
+                let slot = xs.0.parse::<CycleSlot>().ok()?;
+                let mode = SortMode::from_str(xs.1.as_str()).ok()?;
+                Some((slot, mode))
+            })
+            .collect()
+    }
+
+    /// Stamps are readings of the usage clock, so the clock is moved past them.
+    fn read_added(
+        rows: SerializedAdded,
+        clock: &mut u64,
+    ) -> HashMap<CycleSlot, HashMap<String, u64>> {
+        let mut added: HashMap<CycleSlot, HashMap<String, u64>> = HashMap::new();
+        rows.into_iter().for_each(|xs| {
+            let Ok(slot) = xs.0.parse::<CycleSlot>() else {
+                return;
+            };
+            *clock = (*clock).max(xs.2);
+            added.entry(slot).or_default().insert(xs.1, xs.2);
+        });
+        added
+    }
+
+    impl From<&CycleData> for CycleSerialized {
+        fn from(value: &CycleData) -> Self {
+            let profiles = value
+                .profiles
+                .iter()
+                .map(|xs| {
+                    (
+                        xs.name.clone(),
+                        xs.power.clone(),
+                        xs.utility.clone(),
+                        xs.left.clone(),
+                        xs.right.clone(),
+                    )
+                })
+                .collect();
+            let profile_entries = value
+                .profiles
+                .iter()
+                .map(|xs| {
+                    let cycles = [&xs.power, &xs.utility, &xs.left, &xs.right];
+                    (
+                        xs.name.clone(),
+                        flag_rows(&xs.entry_flags, cycles),
+                        sort_mode_rows(&xs.sort_modes),
+                        added_rows(&xs.added, cycles),
+                    )
+                })
+                .collect();
+
+            let live = [&value.power, &value.utility, &value.left, &value.right];
+            let entry_flags = flag_rows(&value.entry_flags, live);
+
+            // We only write usage for items in a cycle or a profile, so the
+            // stats don't grow without bound over a long playthrough.
+            let mut usage: Vec<(String, u32, u64)> = value
+                .usage
+                .iter()
+                .filter(|(spec, _)| {
+                    SLOTS
+                        .iter()
+                        .any(|slot| value.get_cycle(slot).contains(spec))
+                        || value.profiles.iter().any(|profile| {
+                            profile.power.contains(spec)
+                                || profile.utility.contains(spec)
+                                || profile.left.contains(spec)
+                                || profile.right.contains(spec)
+                        })
+                })
+                .map(|(spec, stats)| (spec.clone(), stats.count, stats.last_used))
+                .collect();
+            usage.sort();
+
+            let sort_modes = sort_mode_rows(&value.sort_modes);
+            let added = added_rows(&value.added, live);
+
+            let set_parts = value
+                .equipsets
+                .iter()
+                .flat_map(|set| {
+                    SetPart::ALL.into_iter().filter_map(|part| {
+                        let spec = set.part(part)?;
+                        Some((set.id(), part.to_string(), spec.clone()))
+                    })
+                })
+                .collect();
+
+            let triggers = value
+                .equipsets
+                .iter()
+                .filter(|set| set.auto_equip || !set.triggers.is_empty())
+                .map(|set| {
+                    let names = set.triggers.iter().map(|xs| xs.to_string()).collect();
+                    (set.id(), set.auto_equip, names)
+                })
+                .collect();
+
+            let parents = value
+                .equipsets
+                .iter()
+                .filter_map(|set| Some((set.id(), set.parent?)))
+                .collect();
+
+            let macros = value
+                .macros
+                .iter()
+                .map(|(key, steps)| (*key, steps.iter().map(|xs| xs.to_string()).collect()))
+                .collect();
+
+            Self {
+                base: cosave_v2::CycleSerialized::from(value),
+                profiles,
+                profile_entries,
+                active_profile: value.active_profile.clone(),
+                entry_flags,
+                usage,
+                sort_modes,
+                added,
+                set_parts,
+                triggers,
+                parents,
+                quickslots: value.quickslots.clone(),
+                macros,
+            }
+        }
+    }
+
+    impl From<CycleSerialized> for CycleData {
+        fn from(value: CycleSerialized) -> Self {
+            let mut data: CycleData = value.base.into();
+
+            // Profile contents are checked against the game when the player switches to one.
+            let mut clock = 0;
+            let mut profile_entries = value.profile_entries;
+            data.profiles = value
+                .profiles
+                .into_iter()
+                .map(|xs| {
+                    let mut profile = CycleProfile {
+                        name: xs.0,
+                        power: xs.1,
+                        utility: xs.2,
+                        left: xs.3,
+                        right: xs.4,
+                        ..Default::default()
+                    };
+                    if let Some(idx) = profile_entries.iter().position(|ys| ys.0 == profile.name) {
+                        let (_, flags, modes, added) = profile_entries.swap_remove(idx);
+                        profile.entry_flags = read_flags(flags);
+                        profile.sort_modes = read_sort_modes(modes);
+                        profile.added = read_added(added, &mut clock);
+                    }
+                    profile
+                })
+                .collect();
+            data.active_profile = value.active_profile;
+            data.entry_flags = read_flags(value.entry_flags);
+
+            value.usage.into_iter().for_each(|xs| {
+                data.usage_clock = data.usage_clock.max(xs.2);
+                data.usage.insert(
+                    xs.0,
+                    UsageStats {
+                        count: xs.1,
+                        last_used: xs.2,
+                    },
+                );
+            });
+
+            data.sort_modes = read_sort_modes(value.sort_modes);
+            data.added = read_added(value.added, &mut clock);
+            data.usage_clock = data.usage_clock.max(clock);
+
+            value.set_parts.into_iter().for_each(|(id, part, spec)| {
+                let Ok(part) = SetPart::from_str(part.as_str()) else {
+                    return;
+                };
+                data.set_equipset_part(id, part, Some(spec));
+            });
+
+            value.triggers.into_iter().for_each(|(id, enabled, names)| {
+                let triggers = names
+                    .iter()
+                    .filter_map(|xs| Trigger::parse(xs).ok())
+                    .collect();
+                data.set_equipset_triggers(id, triggers);
+                data.enable_equipset_triggers(id, enabled);
+            });
+
+            value.parents.into_iter().for_each(|(id, parent)| {
+                data.set_equipset_parent(id, Some(parent));
+            });
+
+            data.quickslots = value.quickslots;
+
+            data.macros = value
+                .macros
+                .into_iter()
+                .map(|(key, steps)| {
+                    let steps = steps
+                        .iter()
+                        .filter_map(|text| match text.parse::<MacroStep>() {
+                            Ok(step) => Some(step),
+                            Err(e) => {
+                                log::warn!("Dropping a macro step we can't read; {e:#}");
+                                None
+                            }
+                        })
+                        .collect();
+                    (key, steps)
+                })
+                .collect();
+
+            data
+        }
+    }
+}
+
 pub mod cosave_v2 {
     use bincode::{Decode, Encode};
 
@@ -571,6 +1793,15 @@ pub mod cosave_v2 {
                         )
                     })
                     .collect(),
+                profiles: Vec::new(),
+                active_profile: String::new(),
+                entry_flags: Default::default(),
+                usage: Default::default(),
+                usage_clock: 0,
+                added: Default::default(),
+                sort_modes: Default::default(),
+                quickslots: Vec::new(),
+                macros: Vec::new(),
                 loaded: true,
             }
         }
@@ -673,6 +1904,15 @@ pub mod cosave_v1 {
                     .collect(),
                 hud_visible: value.hud_visible,
                 equipsets: Vec::new(),
+                profiles: Vec::new(),
+                active_profile: String::new(),
+                entry_flags: Default::default(),
+                usage: Default::default(),
+                usage_clock: 0,
+                added: Default::default(),
+                sort_modes: Default::default(),
+                quickslots: Vec::new(),
+                macros: Vec::new(),
                 loaded: true,
             }
         }
@@ -753,6 +1993,15 @@ pub mod cosave_v0 {
                 power: value.power.iter().filter_map(filter_func).collect(),
                 utility: value.utility.iter().filter_map(filter_func).collect(),
                 equipsets: Vec::new(),
+                profiles: Vec::new(),
+                active_profile: String::new(),
+                entry_flags: Default::default(),
+                usage: Default::default(),
+                usage_clock: 0,
+                added: Default::default(),
+                sort_modes: Default::default(),
+                quickslots: Vec::new(),
+                macros: Vec::new(),
                 hud_visible: value.hud_visible,
                 loaded: true,
             }
@@ -765,6 +2014,450 @@ mod tests {
     use super::*;
     use crate::plugin::EquippedData;
 
+    #[test]
+    fn loadouts() {
+        let mut cache = ItemCache::default();
+        let mut cycle = CycleData::default();
+
+        let one = cache.get(&"fake-one".to_string());
+        let two = cache.get(&"fake-two".to_string());
+        cycle.add_item(CycleSlot::Left, &one);
+        cycle.add_item(CycleSlot::Right, &two);
+        cycle.add_equipset(
+            "Bling".to_string(),
+            EquippedData {
+                items: vec!["fake-one".to_string()],
+                empty_slots: vec![13],
+            },
+        );
+        let loadout = cycle.to_loadout();
+        assert_eq!(loadout.left, vec!["fake-one"]);
+        assert_eq!(loadout.equipsets.len(), 1);
+        assert_eq!(loadout.equipsets[0].icon, "armor_heavy");
+
+        // Merging adds new entries after the old and updates sets by name.
+        let mut incoming = loadout.clone();
+        incoming.left = vec!["fake-three".to_string(), "fake-one".to_string()];
+        incoming.power = vec!["fake-four".to_string()];
+        incoming.equipsets[0].empty_slots = vec![];
+        cycle.apply_loadout(incoming.clone(), false, &mut cache);
+        assert_eq!(
+            cycle.formids(&CycleSlot::Left),
+            vec!["fake-one", "fake-three"]
+        );
+        // Fake items are weapons, which can't go in the powers cycle.
+        assert!(cycle.formids(&CycleSlot::Power).is_empty());
+        assert_eq!(cycle.equipset_ids().len(), 1);
+        let set = cycle
+            .equipset_by_id(0)
+            .expect("the set should still be there");
+        assert!(set.empty_slots().is_empty());
+
+        cycle.apply_loadout(incoming, true, &mut cache);
+        assert_eq!(
+            cycle.formids(&CycleSlot::Left),
+            vec!["fake-three", "fake-one"]
+        );
+        assert_eq!(cycle.formids(&CycleSlot::Right), vec!["fake-two"]);
+        assert_eq!(cycle.equipset_names(), vec!["Bling"]);
+    }
+
+    #[test]
+    fn overflow_policies() {
+        let mut cache = ItemCache::default();
+        let mut cycle = CycleData::default();
+        for spec in ["fake-one", "fake-two", "fake-three"] {
+            let item = cache.get(&spec.to_string());
+            cycle.add_item(CycleSlot::Right, &item);
+        }
+
+        assert_eq!(
+            cycle.add_with_overflow(&CycleSlot::Right, "fake-four", 3, OverflowPolicy::Reject),
+            MenuEventResponse::TooManyItems
+        );
+        assert_eq!(cycle.cycle_len(&CycleSlot::Right), 3);
+
+        // The current entry is safe, so the oldest to go is the second one added.
+        assert_eq!(
+            cycle.add_with_overflow(
+                &CycleSlot::Right,
+                "fake-four",
+                3,
+                OverflowPolicy::EvictOldest
+            ),
+            MenuEventResponse::ItemAddedWithEvictions(vec!["fake-two".to_string()])
+        );
+        assert_eq!(
+            cycle.formids(&CycleSlot::Right),
+            vec!["fake-one", "fake-three", "fake-four"]
+        );
+
+        cycle.record_use("fake-three");
+        assert_eq!(
+            cycle.add_with_overflow(
+                &CycleSlot::Right,
+                "fake-five",
+                3,
+                OverflowPolicy::EvictLeastUsed
+            ),
+            MenuEventResponse::ItemAddedWithEvictions(vec!["fake-four".to_string()])
+        );
+
+        // Pinned entries are safe too; a lowered limit evicts more than one.
+        assert!(cycle.set_pinned(&CycleSlot::Right, "fake-three", true));
+        assert_eq!(
+            cycle.add_with_overflow(
+                &CycleSlot::Right,
+                "fake-six",
+                2,
+                OverflowPolicy::EvictOldest
+            ),
+            MenuEventResponse::TooManyItems
+        );
+        assert_eq!(
+            cycle.add_with_overflow(
+                &CycleSlot::Right,
+                "fake-six",
+                3,
+                OverflowPolicy::EvictOldest
+            ),
+            MenuEventResponse::ItemAddedWithEvictions(vec!["fake-five".to_string()])
+        );
+    }
+
+    #[test]
+    fn sorting_cycles() {
+        let mut cache = ItemCache::default();
+        let mut cycle = CycleData::default();
+
+        let specs = ["fake-one", "fake-two", "fake-three"];
+        for spec in specs {
+            let item = cache.get(&spec.to_string());
+            cycle.add_item(CycleSlot::Left, &item);
+        }
+        let mut by_name: Vec<String> = specs.iter().map(|xs| xs.to_string()).collect();
+        by_name.sort_by_key(|xs| cache.get(xs).name().to_lowercase());
+
+        // The current item stays on top; the rest follow in sorted order.
+        cycle.set_top(&CycleSlot::Left, by_name[0].as_str());
+        cycle.sort_cycle(&CycleSlot::Left, SortMode::Name, &mut cache);
+        assert_eq!(cycle.formids(&CycleSlot::Left), by_name);
+        cycle.set_top(&CycleSlot::Left, by_name[1].as_str());
+        cycle.sort_cycle(&CycleSlot::Left, SortMode::Name, &mut cache);
+        assert_eq!(
+            cycle.formids(&CycleSlot::Left),
+            vec![by_name[1].clone(), by_name[2].clone(), by_name[0].clone()]
+        );
+
+        cycle.set_top(&CycleSlot::Left, "fake-one");
+        cycle.record_use("fake-three");
+        cycle.record_use("fake-three");
+        cycle.record_use("fake-two");
+        cycle.sort_cycle(&CycleSlot::Left, SortMode::Frequent, &mut cache);
+        assert_eq!(
+            cycle.formids(&CycleSlot::Left),
+            vec!["fake-one", "fake-three", "fake-two"]
+        );
+        cycle.sort_cycle(&CycleSlot::Left, SortMode::Recent, &mut cache);
+        assert_eq!(
+            cycle.formids(&CycleSlot::Left),
+            vec!["fake-one", "fake-two", "fake-three"]
+        );
+
+        // A kept-applied mode re-sorts when asked to.
+        cycle.set_sort_mode(&CycleSlot::Left, Some(SortMode::Frequent), &mut cache);
+        cycle.record_use("fake-two");
+        cycle.record_use("fake-two");
+        cycle.resort(&mut cache);
+        assert_eq!(
+            cycle.formids(&CycleSlot::Left),
+            vec!["fake-one", "fake-two", "fake-three"]
+        );
+        // Undo puts the kept-applied mode back.
+        let before = cycle.snapshot();
+        cycle.set_sort_mode(&CycleSlot::Left, None, &mut cache);
+        assert_eq!(cycle.sort_mode(&CycleSlot::Left), None);
+        assert_ne!(cycle.snapshot(), before);
+        cycle.restore(before);
+        assert_eq!(cycle.sort_mode(&CycleSlot::Left), Some(SortMode::Frequent));
+    }
+
+    #[test]
+    fn pinned_entries() {
+        let mut cache = ItemCache::default();
+        let mut cycle = CycleData::default();
+
+        let one = cache.get(&"fake-one".to_string());
+        let two = cache.get(&"fake-two".to_string());
+        let three = cache.get(&"fake-three".to_string());
+        cycle.add_item(CycleSlot::Left, &one);
+        cycle.add_item(CycleSlot::Left, &two);
+        cycle.add_item(CycleSlot::Left, &three);
+
+        assert!(!cycle.set_pinned(&CycleSlot::Left, "fake-four", true));
+        assert!(cycle.set_pinned(&CycleSlot::Left, "fake-one", true));
+        assert!(cycle.set_pinned(&CycleSlot::Left, "fake-two", true));
+        assert!(!cycle.entry_flags(&CycleSlot::Left, "fake-one").pinned);
+        assert_eq!(cycle.pinned(&CycleSlot::Left), Some("fake-two".to_string()));
+
+        cycle.advance(&CycleSlot::Left, 2);
+        assert_eq!(
+            cycle.get_top(&CycleSlot::Left),
+            Some("fake-three".to_string())
+        );
+        cycle.return_to_pinned(&CycleSlot::Left, Direction::Forward);
+        assert_eq!(
+            cycle.advance_usable(
+                &CycleSlot::Left,
+                Direction::Forward,
+                &AdvanceFilter::default(),
+                &mut cache
+            ),
+            Some("fake-two".to_string())
+        );
+
+        // Fake items have a count of 2, so they are always usable.
+        assert!(cycle.set_skip_unusable(&CycleSlot::Left, "fake-three", true));
+        assert_eq!(
+            cycle.peek_next_usable(
+                &CycleSlot::Left,
+                Direction::Forward,
+                &AdvanceFilter::default(),
+                &mut cache
+            ),
+            Some("fake-three".to_string())
+        );
+        cache.set_count("fake-three", 0);
+        assert_eq!(
+            cycle.peek_next_usable(
+                &CycleSlot::Left,
+                Direction::Forward,
+                &AdvanceFilter::default(),
+                &mut cache
+            ),
+            Some("fake-one".to_string())
+        );
+        assert_eq!(
+            cycle.advance_usable(
+                &CycleSlot::Left,
+                Direction::Forward,
+                &AdvanceFilter::default(),
+                &mut cache
+            ),
+            Some("fake-one".to_string())
+        );
+
+        // Skipping the other hand's item also passes over unusable entries.
+        let four = cache.get(&"fake-four".to_string());
+        cycle.add_item(CycleSlot::Left, &four);
+        assert_eq!(
+            cycle.advance_skipping(
+                &CycleSlot::Left,
+                two.clone(),
+                Direction::Forward,
+                &AdvanceFilter::default(),
+                &mut cache
+            ),
+            Some("fake-four".to_string())
+        );
+    }
+
+    #[test]
+    fn version_3() {
+        let mut cache = ItemCache::default();
+        let mut cycle = CycleData::default();
+
+        let one = cache.get(&"fake-one".to_string());
+        let two = cache.get(&"fake-two".to_string());
+        cycle.add_item(CycleSlot::Left, &one);
+        assert!(cycle.set_pinned(&CycleSlot::Left, "fake-one", true));
+        assert!(cycle.create_profile("melee".to_string()));
+        cycle.add_item(CycleSlot::Left, &two);
+        assert!(cycle.create_profile("mage".to_string()));
+
+        // Entry flags, usage, and sort modes.
+        cycle.add_item(CycleSlot::Power, &one);
+        cycle.add_item(CycleSlot::Power, &two);
+        cycle.add_item(CycleSlot::Utility, &two);
+        assert!(cycle.set_pinned(&CycleSlot::Power, "fake-two", true));
+        assert!(cycle.set_skip_unusable(&CycleSlot::Utility, "fake-two", true));
+        cycle.record_use("fake-two");
+        cycle.record_use("fake-two");
+        cycle.record_use("fake-one");
+        cycle.record_use("fake-gone");
+        cycle.set_sort_mode(&CycleSlot::Right, Some(SortMode::Frequent), &mut cache);
+
+        // Equipsets with parts, triggers, and parents.
+        for name in ["Battle", "Dungeon", "Hooded"] {
+            cycle.add_equipset(
+                name.to_string(),
+                EquippedData {
+                    items: Vec::new(),
+                    empty_slots: Vec::new(),
+                },
+            );
+        }
+        cycle.set_equipset_part(0, SetPart::Power, Some("fake-shout".to_string()));
+        cycle.set_equipset_part(0, SetPart::Left, Some("fake-spell".to_string()));
+        cycle.set_equipset_triggers(0, vec![Trigger::Combat, Trigger::WeaponsDrawn]);
+        cycle.enable_equipset_triggers(0, true);
+        cycle.set_equipset_triggers(1, vec![Trigger::Location("LocTypeDungeon".to_string())]);
+        assert!(cycle.set_equipset_parent(2, Some(1)));
+
+        // Quickslots and macros.
+        cycle.set_quickslot(59, "Skyrim.esm|0x3eadd".to_string());
+        cycle.set_quickslot(60, "utility:0".to_string());
+        cycle.set_quickslot(59, "health_proxy".to_string());
+        let steps = vec![
+            MacroStep::Equip(CycleSlot::Left, "Skyrim.esm|0x12eb7".to_string()),
+            MacroStep::Advance(CycleSlot::Power),
+            MacroStep::Use("health_proxy".to_string()),
+        ];
+        cycle.set_macro(62, steps.clone());
+
+        let value = cosave_v3::CycleSerialized::from(&cycle);
+        let config = bincode::config::standard();
+        let bytes: Vec<u8> = bincode::encode_to_vec(value, config).unwrap_or_default();
+        let mut decoded = cosave_v3::deserialize(bytes).expect("data should be decodeable");
+
+        assert!(decoded.loaded);
+        assert_eq!(decoded.left.len(), cycle.left.len());
+        assert_eq!(decoded.profile_names(), vec!["melee", "mage"]);
+        assert_eq!(decoded.active_profile(), "mage");
+
+        assert_eq!(
+            decoded.pinned(&CycleSlot::Power),
+            Some("fake-two".to_string())
+        );
+        assert_eq!(decoded.pinned(&CycleSlot::Utility), None);
+        assert_eq!(
+            decoded.skip_unusable_entries(&CycleSlot::Utility),
+            vec!["fake-two".to_string()]
+        );
+
+        assert_eq!(decoded.usage("fake-two").count, 2);
+        // Items in no cycle aren't worth saving stats for.
+        assert_eq!(decoded.usage("fake-gone"), UsageStats::default());
+        assert_eq!(
+            decoded.sort_mode(&CycleSlot::Right),
+            Some(SortMode::Frequent)
+        );
+        assert_eq!(decoded.sort_mode(&CycleSlot::Left), None);
+        assert_eq!(decoded.added, cycle.added);
+        // Each stored profile keeps its own flags and stamps.
+        assert_eq!(decoded.profiles, cycle.profiles);
+        assert_eq!(
+            decoded.profiles[0].entry_flags[&CycleSlot::Left]["fake-one"],
+            EntryFlags {
+                pinned: true,
+                skip_unusable: false
+            }
+        );
+        // The clock picks up where it left off.
+        let last_used = cycle.usage("fake-one").last_used;
+        assert_eq!(decoded.usage("fake-one").last_used, last_used);
+        decoded.record_use("fake-one");
+        assert!(decoded.usage("fake-one").last_used > last_used);
+
+        let battle = decoded
+            .equipset_by_id(0)
+            .expect("the battle set should have survived");
+        assert_eq!(battle.parts(), vec![SetPart::Power, SetPart::Left]);
+        assert_eq!(
+            battle.part(SetPart::Power).map(|xs| xs.as_str()),
+            Some("fake-shout")
+        );
+        // The dungeon set has triggers but isn't switched on.
+        assert_eq!(
+            decoded.triggered_equipsets(),
+            vec![(0, vec![Trigger::Combat, Trigger::WeaponsDrawn])]
+        );
+        assert_eq!(decoded.equipset_children(1), vec![2]);
+
+        assert_eq!(decoded.quickslot(59), Some("health_proxy".to_string()));
+        assert_eq!(decoded.quickslot(60), Some("utility:0".to_string()));
+        assert_eq!(decoded.quickslot(61), None);
+        assert_eq!(decoded.macro_steps(62), Some(steps));
+        assert_eq!(decoded.macro_steps(63), None);
+    }
+
+    #[test]
+    fn profiles() {
+        let mut cache = ItemCache::default();
+        let mut cycle = CycleData::default();
+
+        let one = cache.get(&"fake-one".to_string());
+        let two = cache.get(&"fake-two".to_string());
+        cycle.add_item(CycleSlot::Right, &one);
+        assert!(cycle.create_profile("melee".to_string()));
+        assert!(!cycle.create_profile("melee".to_string()));
+        assert!(!cycle.create_profile("  ".to_string()));
+
+        cycle.clear();
+        cycle.add_item(CycleSlot::Power, &two);
+        assert!(cycle.create_profile("mage".to_string()));
+        assert_eq!(cycle.active_profile(), "mage");
+
+        assert!(cycle.switch_profile("melee"));
+        assert_eq!(cycle.active_profile(), "melee");
+        assert_eq!(cycle.cycle_len(&CycleSlot::Right), 1);
+        assert_eq!(cycle.cycle_len(&CycleSlot::Power), 0);
+
+        // Edits to the live cycles are saved back to the active profile on switch.
+        cycle.add_item(CycleSlot::Left, &two);
+        assert!(cycle.switch_profile("mage"));
+        assert_eq!(cycle.cycle_len(&CycleSlot::Power), 1);
+        assert!(cycle.switch_profile("melee"));
+        assert_eq!(cycle.cycle_len(&CycleSlot::Left), 1);
+
+        // So are pins and sort modes, which don't follow the player to another profile.
+        assert!(cycle.set_pinned(&CycleSlot::Left, "fake-two", true));
+        cycle.set_sort_mode(&CycleSlot::Right, Some(SortMode::Frequent), &mut cache);
+        assert!(cycle.switch_profile("mage"));
+        assert_eq!(cycle.pinned(&CycleSlot::Left), None);
+        assert_eq!(cycle.sort_mode(&CycleSlot::Right), None);
+        assert!(cycle.switch_profile("melee"));
+        assert_eq!(cycle.pinned(&CycleSlot::Left), Some("fake-two".to_string()));
+        assert_eq!(cycle.sort_mode(&CycleSlot::Right), Some(SortMode::Frequent));
+
+        assert!(!cycle.switch_profile("archer"));
+        assert!(cycle.remove_profile("melee"));
+        assert!(!cycle.remove_profile("melee"));
+        assert!(cycle.active_profile().is_empty());
+        assert_eq!(cycle.cycle_len(&CycleSlot::Left), 1);
+    }
+
+    #[test]
+    fn undo_across_profile_switch() {
+        let mut cache = ItemCache::default();
+        let mut cycle = CycleData::default();
+
+        let one = cache.get(&"fake-one".to_string());
+        let two = cache.get(&"fake-two".to_string());
+        assert!(cycle.create_profile("melee".to_string()));
+        cycle.add_item(CycleSlot::Power, &two);
+        assert!(cycle.create_profile("mage".to_string()));
+        assert!(cycle.switch_profile("melee"));
+
+        // Edit melee, switch to mage, then undo both the way the controller does.
+        let before_edit = cycle.snapshot();
+        cycle.add_item(CycleSlot::Left, &one);
+        let before_switch = cycle.snapshot();
+        assert!(cycle.switch_profile("mage"));
+
+        cycle.restore(before_switch);
+        assert_eq!(cycle.active_profile(), "melee");
+        assert_eq!(cycle.cycle_len(&CycleSlot::Left), 1);
+        cycle.restore(before_edit);
+        assert_eq!(cycle.active_profile(), "melee");
+        assert_eq!(cycle.cycle_len(&CycleSlot::Left), 0);
+
+        // Mage is untouched by any of it.
+        assert!(cycle.switch_profile("mage"));
+        assert_eq!(cycle.formids(&CycleSlot::Power), vec!["fake-two"]);
+        assert_eq!(cycle.cycle_len(&CycleSlot::Left), 0);
+    }
+
     #[test]
     fn version_2() {
         let mut cache = ItemCache::default();
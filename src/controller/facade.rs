@@ -7,7 +7,8 @@
 use cxx::CxxVector;
 
 use super::cycles::*;
-use super::settings::{settings, UserSettings};
+use super::settings::{settings, settings_checks, UserSettings};
+use super::watcher;
 use crate::control;
 use crate::data::huditem::RelevantExtraData;
 use crate::data::*;
@@ -44,6 +45,16 @@ pub fn initialize_hud() {
     log::info!("HUD location is: x={}; y={};", hud.anchor.x, hud.anchor.y);
 }
 
+/// No cosave record for us means this character has never used the HUD, so
+/// it gets a template. A new game calls this only once the race menu closes,
+/// so the template matches the race the player picked.
+pub fn apply_starting_template() {
+    let mut ctrl = control::get();
+    if !ctrl.cycles.loaded {
+        ctrl.apply_template();
+    }
+}
+
 /// Function for C++ to call to send a relevant button event to us.
 pub fn handle_key_event(key: u32, button: &ButtonEvent) -> KeyEventResponse {
     control::get().handle_key_event(key, button)
@@ -56,7 +67,11 @@ pub fn handle_key_event(key: u32, button: &ButtonEvent) -> KeyEventResponse {
 #[allow(clippy::boxed_local)]
 pub fn toggle_item(key: u32, #[allow(clippy::boxed_local)] menu_item: Box<HudItem>) {
     let action = Action::from(key);
-    control::get().handle_toggle_item(action, *menu_item)
+    if matches!(action, Action::Quickslot) {
+        control::get().bind_quickslot(key, *menu_item)
+    } else {
+        control::get().handle_toggle_item(action, *menu_item)
+    }
 }
 
 /// Pass along menu events to the controller.
@@ -74,6 +89,11 @@ pub fn refresh_hud_items() {
     control::get().refresh_hud_items();
 }
 
+/// Equip any equipset whose trigger has just started to hold.
+pub fn check_equipset_triggers() {
+    control::get().check_equipset_triggers();
+}
+
 /// Fill out some extra data info.
 pub fn relevant_extra_data(
     has_charge: bool,
@@ -135,9 +155,29 @@ pub fn refresh_user_settings() {
         log::warn!("Failed to read user settings! using defaults; {e:#}");
         return;
     }
+    let settings = settings();
+    if let Some(character) = settings.character_overrides() {
+        log::info!("Effective settings for {character}, with their overrides:");
+        log::info!("{settings}");
+    }
+    settings_checks().report();
     control::get().apply_settings();
 }
 
+/// Reload the settings or the layout if their files changed on disk. Called
+/// periodically by the renderer; does nothing unless watching is turned on.
+pub fn check_watched_files() {
+    let changes = watcher::files_changed();
+    if changes.settings {
+        log::info!("Settings files changed on disk; reloading.");
+        refresh_user_settings();
+    }
+    if changes.layout {
+        log::info!("Layout file changed on disk; reloading.");
+        Layout::refresh();
+    }
+}
+
 /// Clear all cycles. MCM -> this function -> controller.
 pub fn clear_cycles() {
     control::get().clear_cycles();
@@ -151,6 +191,149 @@ pub fn get_cycle_formids(which: i32) -> Vec<String> {
     control::get().cycle_formids(which)
 }
 
+/// Get the names of all saved cycle profiles. Used in MCM.
+pub fn get_cycle_profile_names() -> Vec<String> {
+    control::get().cycle_profile_names()
+}
+
+/// Get the name of the active cycle profile; empty if there isn't one.
+pub fn get_active_cycle_profile() -> String {
+    control::get().cycles.active_profile()
+}
+
+/// Save the current cycles as a new named profile. Used in MCM.
+pub fn handle_create_cycle_profile(name: String) -> bool {
+    control::get().create_cycle_profile(name)
+}
+
+/// Switch to the named cycle profile. Used in MCM.
+pub fn handle_switch_cycle_profile(name: String) -> bool {
+    control::get().switch_cycle_profile(name.as_str())
+}
+
+/// Delete the named cycle profile. Used in MCM.
+pub fn handle_remove_cycle_profile(name: String) -> bool {
+    control::get().remove_cycle_profile(name.as_str())
+}
+
+/// Pin or unpin a cycle entry. Used in MCM.
+pub fn set_cycle_entry_pinned(which: i32, form_spec: String, pinned: bool) -> bool {
+    control::get().set_cycle_entry_pinned(which, form_spec.as_str(), pinned)
+}
+
+/// Skip or stop skipping a cycle entry when it can't be used. Used in MCM.
+pub fn set_cycle_entry_skip_unusable(which: i32, form_spec: String, skip: bool) -> bool {
+    control::get().set_cycle_entry_skip_unusable(which, form_spec.as_str(), skip)
+}
+
+/// Get the form spec of the given cycle's pinned entry. Used in MCM.
+pub fn get_cycle_pinned(which: i32) -> String {
+    control::get().cycle_pinned(which)
+}
+
+/// Get the form specs of the given cycle's skip-when-unusable entries. Used in MCM.
+pub fn get_cycle_skip_unusable(which: i32) -> Vec<String> {
+    control::get().cycle_skip_unusable(which)
+}
+
+/// Make the entry at the given index current in a cycle. Used in MCM.
+pub fn handle_select_cycle_index(which: i32, index: i32) -> bool {
+    let Ok(index) = usize::try_from(index) else {
+        return false;
+    };
+    control::get().select_cycle_index(which, index)
+}
+
+/// Make the equipset at the given index current. Used in MCM.
+pub fn handle_select_equipset_index(index: i32) -> bool {
+    let Ok(index) = usize::try_from(index) else {
+        return false;
+    };
+    control::get().select_equipset_index(index)
+}
+
+/// Sort a cycle by name, category, recent, or frequent use. Used in MCM.
+pub fn handle_sort_cycle(which: i32, mode: String, continuous: bool) -> bool {
+    control::get().sort_cycle(which, mode.as_str(), continuous)
+}
+
+/// Get the sort mode kept applied to a cycle; empty if none. Used in MCM.
+pub fn get_cycle_sort_mode(which: i32) -> String {
+    control::get().cycle_sort_mode(which)
+}
+
+/// Get the parts of the player's gear an equipset changes. Used in MCM.
+pub fn get_equipset_parts(id: u32) -> Vec<String> {
+    control::get().equipset_parts(id)
+}
+
+/// Turn one part of an equipset on or off: armor, power, ammo, left, or right. Used in MCM.
+pub fn handle_set_equipset_part(id: u32, part: String, enabled: bool) -> bool {
+    control::get().set_equipset_part(id, part.as_str(), enabled)
+}
+
+/// Get an equipset's triggers as names, e.g. `combat`. Used in MCM.
+pub fn get_equipset_triggers(id: u32) -> Vec<String> {
+    control::get().equipset_triggers(id)
+}
+
+/// Set an equipset's triggers from a comma-separated list. Used in MCM.
+pub fn handle_set_equipset_triggers(id: u32, triggers: String) -> bool {
+    control::get().set_equipset_triggers(id, triggers.as_str())
+}
+
+/// Are this equipset's triggers switched on? Used in MCM.
+pub fn get_equipset_auto_equip(id: u32) -> bool {
+    control::get().equipset_auto_equip(id)
+}
+
+/// Switch this equipset's triggers on or off. Used in MCM.
+pub fn handle_set_equipset_auto_equip(id: u32, enabled: bool) -> bool {
+    control::get().set_equipset_auto_equip(id, enabled)
+}
+
+/// Get the id of the equipset this one is based on, or -1 if none. Used in MCM.
+pub fn get_equipset_parent(id: u32) -> i32 {
+    control::get().equipset_parent(id)
+}
+
+/// Base an equipset on another, or pass -1 to make it stand alone. Used in MCM.
+pub fn handle_set_equipset_parent(id: u32, parent: i32) -> bool {
+    control::get().set_equipset_parent(id, parent)
+}
+
+/// Get the names of the loadout files available to import. Used in MCM.
+pub fn get_loadout_names() -> Vec<String> {
+    control::get().loadout_names()
+}
+
+/// Write cycles and equipsets to the named loadout file. Used in MCM.
+pub fn handle_export_loadout(name: String) -> bool {
+    control::get().export_loadout(name.as_str())
+}
+
+/// Merge in or replace with the named loadout. Returns the number of entries
+/// the game couldn't find, or -1 if the file couldn't be read. Used in MCM.
+pub fn handle_import_loadout(name: String, replace: bool) -> i32 {
+    control::get().import_loadout(name.as_str(), replace)
+}
+
+/// Get the names of the auto-fill rules in the settings file. Used in MCM.
+pub fn get_auto_fill_rule_names() -> Vec<String> {
+    control::get().auto_fill_rule_names()
+}
+
+/// Fill a cycle using the named auto-fill rule. Returns the number of items
+/// added, or -1 if the rule couldn't be used. Used in MCM.
+pub fn handle_auto_fill(name: String) -> i32 {
+    control::get().auto_fill(name.as_str())
+}
+
+/// Describe each problem found in the settings file. Used in MCM.
+pub fn get_settings_problems() -> Vec<String> {
+    settings_checks().lines()
+}
+
 pub fn serialize_version() -> u32 {
     CycleData::serialize_version()
 }
@@ -173,8 +356,12 @@ pub fn cycle_loaded_from_cosave(bytes: &CxxVector<u8>, version: u32) {
     }
 }
 
+/// Called when the game reverts before a load. Whatever loads next has to
+/// bring its own cycles, or it gets a template.
 pub fn clear_cache() {
-    control::get().cache.clear();
+    let mut ctrl = control::get();
+    ctrl.cache.clear();
+    ctrl.cycles.loaded = false;
 }
 
 /// Crash logger support.
@@ -209,6 +396,7 @@ pub fn equipset_index_to_id(idx: String) -> i32 {
     }
 }
 
+/// Equipset names in id order. Sets based on another show it, as in `Child < Parent`.
 pub fn get_equipset_names() -> Vec<String> {
     control::get().cycles.equipset_names()
 }
@@ -224,22 +412,22 @@ pub fn get_equipset_ids() -> Vec<String> {
 
 pub fn handle_create_equipset(name: String) -> bool {
     let data = getEquippedItems();
-    control::get().cycles.add_equipset(name, *data)
+    control::get().create_equipset(name, *data)
 }
 
 pub fn handle_update_equipset(id: u32) -> bool {
     let data = getEquippedItems();
-    control::get().cycles.update_equipset(id, *data)
+    control::get().update_equipset(id, *data)
 }
 
 /// Rename the equipset with the given ID.
 pub fn handle_rename_equipset(id: u32, name: String) -> bool {
-    control::get().cycles.rename_equipset(id, name)
+    control::get().rename_equipset(id, name)
 }
 
 /// Remove the equipset with the given ID.
 pub fn handle_remove_equipset(id: u32) -> bool {
-    control::get().cycles.remove_equipset(id.to_string())
+    control::get().remove_equipset(id)
 }
 
 /// Create the equipped data struct.
@@ -252,13 +440,20 @@ pub fn get_equipset_item_names(id: u32) -> Vec<String> {
     control::get().get_equipset_item_names(id)
 }
 
+/// Describe what's missing from the equipment set with the given id, one
+/// line per missing item. Empty if nothing is wrong.
+pub fn get_equipset_problems(id: u32) -> Vec<String> {
+    control::get().get_equipset_problems(id)
+}
+
 /// Use the icon from the named item for the equipment set with the given id.
 pub fn set_equipset_icon(id: u32, itemname: String) -> bool {
     control::get().set_equipset_icon(id, itemname)
 }
 
 /// Look up an equipset by name, returning its id. Since uniqueness is not
-/// really enforced for names, this returns the first one found.
+/// really enforced for names, this returns the first one found. Accepts the
+/// names `get_equipset_names()` shows for sets with parents.
 pub fn look_up_equipset_by_name(name: String) -> u32 {
     control::get().cycles.equipset_by_name(name)
 }
@@ -0,0 +1,116 @@
+//! Rules for entries to pass over when advancing a cycle. Each rule is
+//! switched on or off by its own setting, and the rules are read fresh
+//! from settings on every advance so changes apply immediately.
+
+use super::settings::UserSettings;
+use crate::data::HudItem;
+#[cfg(not(test))]
+use crate::plugin::{canAffordSpell, hasItemOrSpell, itemCount};
+
+/// The set of skip rules in effect for one advance.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AdvanceFilter {
+    /// Skip utility items with a count of zero.
+    pub empty_utilities: bool,
+    /// Skip spells the player doesn't have the magicka to cast.
+    pub unaffordable_spells: bool,
+    /// Skip items the player no longer has.
+    pub lost_items: bool,
+}
+
+impl AdvanceFilter {
+    pub fn from_settings(settings: &UserSettings) -> Self {
+        Self {
+            empty_utilities: settings.skip_empty_utilities(),
+            unaffordable_spells: settings.skip_unaffordable_spells(),
+            lost_items: settings.skip_lost_items(),
+        }
+    }
+
+    /// True if any rule says this cycle entry should be passed over.
+    /// The cheap checks go first so we ask the game as rarely as we can.
+    pub fn skip(&self, item: &HudItem) -> bool {
+        if self.empty_utilities
+            && item.kind().is_utility()
+            && item.count_matters()
+            && item.count() == 0
+        {
+            return true;
+        }
+
+        let form_spec = item.form_string();
+        if self.unaffordable_spells && item.kind().is_spell() && !can_afford(&form_spec) {
+            return true;
+        }
+        if self.lost_items && !still_has(&form_spec) {
+            log::debug!(
+                "skipping an item the player no longer has; name='{}';",
+                item.name()
+            );
+            return true;
+        }
+
+        false
+    }
+}
+
+// The functions below ask the game about the player. Tests get stand-ins
+// that never reach across the bridge.
+
+#[cfg(not(test))]
+fn can_afford(form_spec: &str) -> bool {
+    cxx::let_cxx_string!(form_spec = form_spec);
+    canAffordSpell(&form_spec)
+}
+
+#[cfg(test)]
+fn can_afford(_form_spec: &str) -> bool {
+    true
+}
+
+/// Same test `CycleData::validate()` uses to decide what to keep.
+#[cfg(not(test))]
+fn still_has(form_spec: &str) -> bool {
+    cxx::let_cxx_string!(form_spec = form_spec);
+    hasItemOrSpell(&form_spec) || itemCount(&form_spec) > 0
+}
+
+#[cfg(test)]
+fn still_has(_form_spec: &str) -> bool {
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::item_cache::ItemCache;
+    use crate::data::potion::PotionType;
+    use crate::data::BaseType;
+
+    #[test]
+    fn empty_utilities_rule() {
+        let mut cache = ItemCache::new();
+        let weapon = cache.get(&"fake-weapon".to_string());
+        let potion = HudItem::preclassified(
+            "Potion of Plenty".to_string(),
+            "fake-potion".to_string(),
+            0,
+            BaseType::Potion(PotionType::Health),
+        );
+
+        let filter = AdvanceFilter {
+            empty_utilities: true,
+            ..Default::default()
+        };
+        assert!(filter.skip(&potion));
+        assert!(!AdvanceFilter::default().skip(&potion));
+        let mut restocked = potion.clone();
+        restocked.set_count(3);
+        assert!(!filter.skip(&restocked));
+
+        // Weapons aren't utilities, so this rule never applies to them.
+        let mut broke = weapon.clone();
+        broke.set_count(0);
+        assert!(!filter.skip(&broke));
+    }
+}
@@ -0,0 +1,124 @@
+//! A bounded undo/redo history. Each entry is a snapshot of whatever was
+//! about to change, paired with a short description of the change for
+//! notifications.
+
+use std::collections::VecDeque;
+
+/// The most changes we remember. Older changes fall off the end.
+pub const HISTORY_LIMIT: usize = 20;
+
+#[derive(Debug, Clone)]
+pub struct History<T> {
+    /// Snapshots taken before each change, most recent last.
+    undo: VecDeque<(String, T)>,
+    /// Snapshots taken before each undo, most recent last.
+    redo: Vec<(String, T)>,
+    limit: usize,
+}
+
+impl<T> Default for History<T> {
+    fn default() -> Self {
+        Self::new(HISTORY_LIMIT)
+    }
+}
+
+impl<T> History<T> {
+    pub fn new(limit: usize) -> Self {
+        Self {
+            undo: VecDeque::new(),
+            redo: Vec::new(),
+            limit,
+        }
+    }
+
+    /// Remember the state from before a change. Making a new change
+    /// abandons anything that could have been redone.
+    pub fn push(&mut self, label: String, before: T) {
+        self.redo.clear();
+        self.undo.push_back((label, before));
+        while self.undo.len() > self.limit {
+            self.undo.pop_front();
+        }
+    }
+
+    /// Step back one change. Takes the current state so it can be redone, and
+    /// returns the description of the change and the state to restore.
+    pub fn undo(&mut self, current: T) -> Option<(String, T)> {
+        let (label, before) = self.undo.pop_back()?;
+        self.redo.push((label.clone(), current));
+        Some((label, before))
+    }
+
+    /// Step forward one undone change. Takes the current state so it can be
+    /// undone again, and returns the description and the state to restore.
+    pub fn redo(&mut self, current: T) -> Option<(String, T)> {
+        let (label, after) = self.redo.pop()?;
+        self.undo.push_back((label.clone(), current));
+        Some((label, after))
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// Forget everything, e.g., when a save is loaded.
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn undo_and_redo() {
+        let mut history: History<Vec<u32>> = History::new(3);
+        let mut state = vec![1];
+        assert!(history.undo(state.clone()).is_none());
+
+        history.push("add two".to_string(), state.clone());
+        state.push(2);
+        history.push("add three".to_string(), state.clone());
+        state.push(3);
+
+        let (label, before) = history
+            .undo(state.clone())
+            .expect("there is a change to undo");
+        assert_eq!(label, "add three");
+        state = before;
+        assert_eq!(state, vec![1, 2]);
+        assert!(history.can_redo());
+
+        let (label, after) = history
+            .redo(state.clone())
+            .expect("there is a change to redo");
+        assert_eq!(label, "add three");
+        state = after;
+        assert_eq!(state, vec![1, 2, 3]);
+
+        // A fresh change means the undone one can't come back.
+        history
+            .undo(state.clone())
+            .expect("there is a change to undo");
+        history.push("add four".to_string(), vec![1, 2]);
+        assert!(!history.can_redo());
+    }
+
+    #[test]
+    fn history_is_bounded() {
+        let mut history: History<u32> = History::new(2);
+        for value in 0..5 {
+            history.push(format!("change {value}"), value);
+        }
+        assert_eq!(history.undo(5).map(|xs| xs.1), Some(4));
+        assert_eq!(history.undo(4).map(|xs| xs.1), Some(3));
+        assert!(history.undo(3).is_none());
+        assert!(!history.can_undo());
+    }
+}
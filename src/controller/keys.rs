@@ -6,13 +6,14 @@ use std::time::{Duration, Instant};
 
 use enumset::{EnumSet, EnumSetType};
 use eyre::eyre;
-use strum::Display;
+use strum::{Display, EnumString};
 
 use super::control::RequestedAction;
 use super::settings::{settings, ActivationMethod, UnarmedMethod};
 use crate::plugin::{hasRangedEquipped, Action, ButtonEvent, HudElement};
 
-#[derive(Debug, Clone, Hash, PartialEq, Eq, Display)]
+#[derive(Debug, Clone, Hash, PartialEq, Eq, Display, EnumString)]
+#[strum(ascii_case_insensitive)]
 pub enum CycleSlot {
     Left,
     Power,
@@ -26,6 +27,7 @@ pub enum Modifier {
     Cycle,
     Activate,
     Menu,
+    Reverse,
 }
 
 impl Modifier {
@@ -36,6 +38,7 @@ impl Modifier {
             Modifier::Cycle => options.cycle_modifier(),
             Modifier::Activate => options.activate_modifier(),
             Modifier::Menu => options.menu_modifier(),
+            Modifier::Reverse => options.reverse_modifier(),
         }
     }
 }
@@ -51,6 +54,12 @@ pub enum Hotkey {
     UnequipHands,
     Refresh,
     ShowHide,
+    Undo,
+    Redo,
+    EquipSetSwap,
+    Quickslot(u32), // carries its key code
+    MacroRecord,
+    Macro(u32),                  // carries its key code
     Modifier(EnumSet<Modifier>), // for overloaded modifiers
     #[default]
     None,
@@ -77,6 +86,11 @@ impl From<u32> for Hotkey {
         if options.menu_modifier().is_positive() && v == options.menu_modifier().unsigned_abs() {
             set.insert(Modifier::Menu);
         }
+        if options.reverse_modifier().is_positive()
+            && v == options.reverse_modifier().unsigned_abs()
+        {
+            set.insert(Modifier::Reverse);
+        }
 
         if !set.is_empty() {
             Hotkey::Modifier(set)
@@ -98,6 +112,18 @@ impl From<u32> for Hotkey {
             Hotkey::Activate
         } else if v == options.unequip_hotkey() as u32 {
             Hotkey::UnequipHands
+        } else if v == options.undo_key() as u32 {
+            Hotkey::Undo
+        } else if v == options.redo_key() as u32 {
+            Hotkey::Redo
+        } else if v == options.equipset_swap_key() as u32 {
+            Hotkey::EquipSetSwap
+        } else if v == options.macro_record_key() as u32 {
+            Hotkey::MacroRecord
+        } else if options.is_quickslot_key(v) {
+            Hotkey::Quickslot(v)
+        } else if options.is_macro_key(v) {
+            Hotkey::Macro(v)
         } else {
             Hotkey::None
         }
@@ -118,6 +144,12 @@ impl Hotkey {
             Hotkey::UnequipHands => options.unequip_hotkey() as i32,
             Hotkey::Refresh => options.refresh_layout() as i32,
             Hotkey::ShowHide => options.showhide() as i32,
+            Hotkey::Undo => options.undo_key(),
+            Hotkey::Redo => options.redo_key(),
+            Hotkey::EquipSetSwap => options.equipset_swap_key(),
+            Hotkey::Quickslot(key) => *key as i32,
+            Hotkey::MacroRecord => options.macro_record_key(),
+            Hotkey::Macro(key) => *key as i32,
             Hotkey::Modifier(meanings) => {
                 // This is going to map to a single re-used key.
                 if let Some(meaning) = meanings.iter().find_map(Some) {
@@ -130,8 +162,13 @@ impl Hotkey {
         }
     }
 
+    /// What a long press of this hotkey should do. An explicit binding in
+    /// the settings wins; otherwise the older on/off settings decide.
     pub fn long_press_action(&self) -> RequestedAction {
         let settings = settings();
+        if let Some(bound) = settings.long_press_binding(self) {
+            return bound;
+        }
         let advance = matches!(settings.cycle_advance_method(), ActivationMethod::LongPress);
         let unequip = matches!(settings.unequip_method(), UnarmedMethod::LongPress);
 
@@ -170,6 +207,8 @@ impl Hotkey {
             } else {
                 RequestedAction::None
             }
+        } else if matches!(self, Hotkey::Equipment) && settings.long_press_swaps_equipset() {
+            RequestedAction::SwapEquipSet
         } else {
             RequestedAction::None
         }
@@ -200,6 +239,10 @@ impl From<&Action> for Hotkey {
             Action::Utility => Hotkey::Utility,
             Action::RefreshLayout => Hotkey::Refresh,
             Action::UnequipHands => Hotkey::UnequipHands,
+            Action::Undo => Hotkey::Undo,
+            Action::Redo => Hotkey::Redo,
+            Action::EquipSetSwap => Hotkey::EquipSetSwap,
+            Action::MacroRecord => Hotkey::MacroRecord,
             _ => Hotkey::None,
         }
     }
@@ -236,6 +279,8 @@ pub struct TrackedKey {
     pub state: KeyState,
     /// When we started tracking this key.
     pub press_start: Option<Instant>,
+    /// True if this press started soon enough after the last one to make a double tap.
+    double_tap: bool,
 }
 
 impl TrackedKey {
@@ -249,6 +294,7 @@ impl TrackedKey {
             hotkey,
             state,
             press_start,
+            double_tap: false,
         }
     }
 
@@ -271,6 +317,10 @@ impl TrackedKey {
         )
     }
 
+    pub fn is_quickslot(&self) -> bool {
+        matches!(self.hotkey, Hotkey::Quickslot(_))
+    }
+
     pub fn update(&mut self, event: &ButtonEvent) {
         self.state = KeyState::from(event);
         match self.state {
@@ -283,11 +333,29 @@ impl TrackedKey {
                 }
             }
             KeyState::Down => {
-                self.press_start = Some(Instant::now());
+                let window = Duration::from_millis(settings().double_tap_ms().into());
+                self.note_press(Instant::now(), window);
             }
         }
     }
 
+    /// Record the start of a fresh press. It's a double tap if the previous
+    /// press started within the window, unless that press was itself the
+    /// second half of a double tap.
+    fn note_press(&mut self, now: Instant, window: Duration) {
+        self.double_tap = !self.double_tap
+            && self
+                .press_start
+                .is_some_and(|start| now.duration_since(start) <= window);
+        self.press_start = Some(now);
+    }
+
+    /// Is this press the second of two quick taps? Stays true until the key
+    /// goes down again.
+    pub fn is_double_tap(&self) -> bool {
+        self.double_tap
+    }
+
     pub fn is_long_press(&self) -> bool {
         if let Some(start) = self.press_start {
             let elapsed_time = start.elapsed();
@@ -313,6 +381,7 @@ impl Default for TrackedKey {
             hotkey: Hotkey::None,
             state: KeyState::Up,
             press_start: None,
+            double_tap: false,
         }
     }
 }
@@ -334,6 +403,12 @@ impl From<&Hotkey> for Action {
             Hotkey::Refresh => Action::RefreshLayout,
             Hotkey::ShowHide => Action::ShowHide,
             Hotkey::Equipment => Action::Equipment,
+            Hotkey::Undo => Action::Undo,
+            Hotkey::Redo => Action::Redo,
+            Hotkey::EquipSetSwap => Action::EquipSetSwap,
+            Hotkey::Quickslot(_) => Action::Quickslot,
+            Hotkey::MacroRecord => Action::MacroRecord,
+            Hotkey::Macro(_) => Action::Macro,
             _ => Action::None,
         }
     }
@@ -366,6 +441,21 @@ impl TryFrom<Action> for CycleSlot {
     }
 }
 
+/// Papyrus identifies cycles by number, in the order the MCM lists them.
+impl TryFrom<i32> for CycleSlot {
+    type Error = eyre::Error;
+
+    fn try_from(value: i32) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(CycleSlot::Power),
+            1 => Ok(CycleSlot::Utility),
+            2 => Ok(CycleSlot::Left),
+            3 => Ok(CycleSlot::Right),
+            _ => Err(eyre!("this number does not map to a cycle; which={value}")),
+        }
+    }
+}
+
 impl TryFrom<Hotkey> for CycleSlot {
     type Error = eyre::Error;
 
@@ -401,3 +491,25 @@ impl From<CycleSlot> for Action {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn double_taps() {
+        let mut tracked = TrackedKey::default();
+        let window = Duration::from_millis(300);
+        let start = Instant::now();
+
+        tracked.note_press(start, window);
+        assert!(!tracked.is_double_tap());
+        tracked.note_press(start + Duration::from_millis(200), window);
+        assert!(tracked.is_double_tap());
+        // A third quick tap starts over instead of counting twice.
+        tracked.note_press(start + Duration::from_millis(400), window);
+        assert!(!tracked.is_double_tap());
+        tracked.note_press(start + Duration::from_millis(1000), window);
+        assert!(!tracked.is_double_tap());
+    }
+}
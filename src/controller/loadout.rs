@@ -0,0 +1,327 @@
+//! Loadouts: cycles and equipment sets written out as toml so players can
+//! edit them by hand, carry them between saves, and share them.
+//!
+//! A loadout file looks like this:
+//!
+//! ```toml
+//! power = ["Skyrim.esm|0x000E40C8"]
+//! utility = ["health_proxy"]
+//! left = ["Skyrim.esm|0x00012EB7"]
+//! right = ["Skyrim.esm|0x00012EB7", "Skyrim.esm|0x0001397E"]
+//!
+//! [[equipset]]
+//! name = "Bandit Chief"
+//! icon = "armor_heavy"
+//! items = ["Skyrim.esm|0x00012E4D", "Skyrim.esm|0x00012E4B"]
+//! empty_slots = [13]
+//! power = "Skyrim.esm|0x00013E07"
+//! ```
+//!
+//! Every field is optional, so a file can hold just a cycle or two. An
+//! equipment set may also name a `power`, `ammo`, `left`, or `right` item;
+//! parts it leaves out are left alone when the set is equipped. Sets can list
+//! `triggers` and turn them on with `auto_equip = true`, and a set can name a
+//! `parent` set whose items it overrides.
+
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use eyre::{eyre, Context, Result};
+use serde::{Deserialize, Serialize};
+
+use super::cycleentries::{EquipSet, SetPart};
+use super::triggers::Trigger;
+
+/// Where loadout files live.
+pub static LOADOUT_DIR: &str = "./data/SKSE/Plugins/SoulsyHUD/loadouts";
+/// Where starting templates for new characters live. Templates are loadout
+/// files named for a tag from the settings file, for a race's editor id
+/// (e.g. `NordRace.toml`), or `default.toml`.
+pub static TEMPLATE_DIR: &str = "./data/SKSE/Plugins/SoulsyHUD/templates";
+
+/// The editable form of a player's cycles and equipment sets.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct Loadout {
+    #[serde(default)]
+    pub power: Vec<String>,
+    #[serde(default)]
+    pub utility: Vec<String>,
+    #[serde(default)]
+    pub left: Vec<String>,
+    #[serde(default)]
+    pub right: Vec<String>,
+    #[serde(default, rename = "equipset")]
+    pub equipsets: Vec<LoadoutSet>,
+}
+
+/// One equipment set in a loadout. Sets get fresh ids when imported, so the
+/// name is what identifies them.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct LoadoutSet {
+    pub name: String,
+    /// The name of an icon, e.g. `armor_heavy`. Unknown names fall back to that.
+    #[serde(default)]
+    pub icon: String,
+    #[serde(default)]
+    pub items: Vec<String>,
+    /// Biped slots this set leaves empty.
+    #[serde(default)]
+    pub empty_slots: Vec<u8>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub power: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ammo: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub left: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub right: Option<String>,
+    /// Trigger names, e.g. `combat`. Names we don't know are dropped on import.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub triggers: Vec<String>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub auto_equip: bool,
+    /// The name of the set this one is based on.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent: Option<String>,
+}
+
+impl From<&EquipSet> for LoadoutSet {
+    fn from(value: &EquipSet) -> Self {
+        Self {
+            name: value.name(),
+            icon: value.icon().to_string(),
+            items: value.items().to_vec(),
+            empty_slots: value.empty_slots().to_vec(),
+            power: value.part(SetPart::Power).cloned(),
+            ammo: value.part(SetPart::Ammo).cloned(),
+            left: value.part(SetPart::Left).cloned(),
+            right: value.part(SetPart::Right).cloned(),
+            triggers: value.triggers.iter().map(|xs| xs.to_string()).collect(),
+            auto_equip: value.auto_equip,
+            parent: None,
+        }
+    }
+}
+
+impl LoadoutSet {
+    /// Copy the hand, power, and ammo parts and the triggers onto an equipment set.
+    pub fn apply_to(&self, set: &mut EquipSet) {
+        set.set_part(SetPart::Power, self.power.clone());
+        set.set_part(SetPart::Ammo, self.ammo.clone());
+        set.set_part(SetPart::Left, self.left.clone());
+        set.set_part(SetPart::Right, self.right.clone());
+        set.triggers = self
+            .triggers
+            .iter()
+            .filter_map(|xs| Trigger::parse(xs).ok())
+            .collect();
+        set.auto_equip = self.auto_equip;
+    }
+}
+
+impl Loadout {
+    /// Read a loadout from a toml file.
+    pub fn read_from_file(path: &Path) -> Result<Self> {
+        let buf = fs::read_to_string(path)
+            .wrap_err_with(|| format!("Unable to read the loadout file: {}", path.display()))?;
+        let loadout = toml::from_str::<Loadout>(&buf)
+            .wrap_err_with(|| format!("Unable to parse the loadout file: {}", path.display()))?;
+        Ok(loadout)
+    }
+
+    /// Write this loadout to a toml file, making its directory if needed.
+    pub fn write_to_file(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let buf = toml::to_string_pretty(self)?;
+        let mut fp = fs::File::create(path)?;
+        write!(fp, "{buf}")?;
+        Ok(())
+    }
+
+    /// Remove every form spec the lookup can't find, returning the ones
+    /// removed. Each missing spec is reported once even if it appears in
+    /// several places.
+    pub fn retain_found(&mut self, mut found: impl FnMut(&str) -> bool) -> Vec<String> {
+        let mut missing: Vec<String> = Vec::new();
+        let mut check = |specs: &mut Vec<String>| {
+            specs.retain(|spec| {
+                if missing.contains(spec) {
+                    false
+                } else if found(spec) {
+                    true
+                } else {
+                    missing.push(spec.clone());
+                    false
+                }
+            });
+        };
+        check(&mut self.power);
+        check(&mut self.utility);
+        check(&mut self.left);
+        check(&mut self.right);
+        self.equipsets.iter_mut().for_each(|set| {
+            check(&mut set.items);
+            for part in [&mut set.power, &mut set.ammo, &mut set.left, &mut set.right] {
+                let mut specs: Vec<String> = part.take().into_iter().collect();
+                check(&mut specs);
+                *part = specs.pop();
+            }
+        });
+        missing
+    }
+}
+
+/// Get the path for a named loadout. Names may only use letters, numbers,
+/// spaces, dashes, and underscores, so a loadout can't escape its directory.
+pub fn loadout_path(name: &str) -> Result<PathBuf> {
+    path_in(Path::new(LOADOUT_DIR), name)
+}
+
+fn path_in(dir: &Path, name: &str) -> Result<PathBuf> {
+    let name = name.trim();
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|xs| xs.is_alphanumeric() || matches!(xs, ' ' | '-' | '_'));
+    if !valid {
+        return Err(eyre!("'{name}' can't be used as a loadout name"));
+    }
+    Ok(dir.join(format!("{name}.toml")))
+}
+
+/// Find the starting template for a character. A template named for the tag
+/// wins, then one named for the race, then the default. Returns the name of
+/// the template chosen along with its contents.
+pub fn find_template(tag: &str, race: &str) -> Option<(String, Loadout)> {
+    find_template_in(Path::new(TEMPLATE_DIR), tag, race)
+}
+
+fn find_template_in(dir: &Path, tag: &str, race: &str) -> Option<(String, Loadout)> {
+    [tag, race, "default"]
+        .into_iter()
+        .filter(|name| !name.trim().is_empty())
+        .find_map(|name| {
+            let path = path_in(dir, name).ok()?;
+            if !path.exists() {
+                return None;
+            }
+            match Loadout::read_from_file(&path) {
+                Ok(template) => Some((name.to_string(), template)),
+                Err(e) => {
+                    log::warn!("Skipping a template that can't be read; {e:#}");
+                    None
+                }
+            }
+        })
+}
+
+/// Get the names of all loadout files, sorted.
+pub fn loadout_names() -> Vec<String> {
+    let Ok(entries) = fs::read_dir(LOADOUT_DIR) else {
+        return Vec::new();
+    };
+    let mut names: Vec<String> = entries
+        .filter_map(|entry| {
+            let path = entry.ok()?.path();
+            if path.extension()? != "toml" {
+                return None;
+            }
+            Some(path.file_stem()?.to_string_lossy().to_string())
+        })
+        .collect();
+    names.sort();
+    names
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn loadouts_round_trip() {
+        let loadout = Loadout {
+            power: vec!["Skyrim.esm|0x000E40C8".to_string()],
+            utility: vec!["health_proxy".to_string()],
+            left: Vec::new(),
+            right: vec!["Skyrim.esm|0x00012EB7".to_string()],
+            equipsets: vec![LoadoutSet {
+                name: "Bandit Chief".to_string(),
+                icon: "armor_heavy".to_string(),
+                items: vec!["Skyrim.esm|0x00012E4D".to_string()],
+                empty_slots: vec![13],
+                power: Some("Skyrim.esm|0x00013E07".to_string()),
+                ..Default::default()
+            }],
+        };
+        let buf = toml::to_string_pretty(&loadout).expect("loadouts should serialize");
+        let parsed: Loadout = toml::from_str(&buf).expect("loadouts should parse");
+        assert_eq!(parsed, loadout);
+
+        let sparse: Loadout =
+            toml::from_str("left = [\"unarmed_proxy\"]\n").expect("fields should be optional");
+        assert_eq!(sparse.left, vec!["unarmed_proxy"]);
+        assert!(sparse.equipsets.is_empty());
+    }
+
+    #[test]
+    fn missing_entries_are_reported() {
+        let mut loadout = Loadout {
+            left: vec!["Gone.esp|0x801".to_string(), "Skyrim.esm|0x1".to_string()],
+            right: vec!["Gone.esp|0x801".to_string()],
+            equipsets: vec![LoadoutSet {
+                name: "Mixed".to_string(),
+                items: vec!["Skyrim.esm|0x2".to_string(), "Gone.esp|0x802".to_string()],
+                ammo: Some("Gone.esp|0x803".to_string()),
+                right: Some("Skyrim.esm|0x3".to_string()),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let missing = loadout.retain_found(|spec| !spec.starts_with("Gone.esp"));
+        assert_eq!(
+            missing,
+            vec!["Gone.esp|0x801", "Gone.esp|0x802", "Gone.esp|0x803"]
+        );
+        assert_eq!(loadout.left, vec!["Skyrim.esm|0x1"]);
+        assert!(loadout.right.is_empty());
+        assert_eq!(loadout.equipsets[0].items, vec!["Skyrim.esm|0x2"]);
+        assert!(loadout.equipsets[0].ammo.is_none());
+        assert_eq!(
+            loadout.equipsets[0].right.as_deref(),
+            Some("Skyrim.esm|0x3")
+        );
+    }
+
+    #[test]
+    fn choosing_templates() {
+        let dir = Path::new("./tests/fixtures/templates");
+        let (name, template) =
+            find_template_in(dir, "", "NordRace").expect("the race template should be found");
+        assert_eq!(name, "NordRace");
+        assert_eq!(template.right, vec!["Skyrim.esm|0x00013989"]);
+
+        let (name, _) =
+            find_template_in(dir, "Mage", "NordRace").expect("the tag template should be found");
+        assert_eq!(name, "Mage");
+
+        let (name, template) = find_template_in(dir, "Nonesuch", "ArgonianRace")
+            .expect("the default template should be found");
+        assert_eq!(name, "default");
+        assert_eq!(
+            template.utility,
+            vec!["health_proxy", "magicka_proxy", "stamina_proxy"]
+        );
+
+        assert!(find_template_in(Path::new("./tests/fixtures/nowhere"), "", "NordRace").is_none());
+    }
+
+    #[test]
+    fn loadout_names_stay_in_their_directory() {
+        assert!(loadout_path("Bandit Chief").is_ok());
+        assert!(loadout_path("../settings").is_err());
+        assert!(loadout_path("").is_err());
+    }
+}
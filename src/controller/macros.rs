@@ -0,0 +1,237 @@
+//! Recorded macros: a short list of HUD actions replayed on a hotkey.
+//!
+//! Press the macro record key (`iMacroRecordKey`) to start recording. Cycle
+//! advances, equips, utility uses, and equipment sets are noted as they
+//! happen. Press one of the macro keys to stop and save the recording on that
+//! key; press the record key again to throw it away. Macro keys are listed in
+//! the `[Macros]` section of the settings file, by key code, with a name to
+//! show the player:
+//!
+//! ```ini
+//! [Macros]
+//! 62 = Shield and ward
+//! ```
+//!
+//! Pressing a macro key plays its steps back one at a time, paced by
+//! `uMacroStepMillis`. Any other HUD hotkey, or the same macro key, stops a
+//! macro partway. Macros are saved with the character.
+
+use std::collections::VecDeque;
+use std::fmt::Display;
+use std::str::FromStr;
+
+use eyre::{eyre, Result};
+
+use super::keys::CycleSlot;
+
+/// The most steps one macro may hold. Recording stops adding past this.
+pub const MAX_MACRO_STEPS: usize = 32;
+
+/// One thing a macro does.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum MacroStep {
+    /// Advance a cycle, as if its hotkey was tapped.
+    Advance(CycleSlot),
+    /// Equip this form spec into the slot for a cycle.
+    Equip(CycleSlot, String),
+    /// Use a utility item, by form spec.
+    Use(String),
+    /// Equip the equipment set with this id.
+    EquipSet(u32),
+}
+
+fn slot_from_name(name: &str) -> Result<CycleSlot> {
+    name.trim()
+        .parse::<CycleSlot>()
+        .map_err(|_| eyre!("'{name}' is not a cycle"))
+}
+
+/// Steps are saved as text, e.g. `advance:left` or `equip:right:Skyrim.esm|0x12eb7`.
+impl FromStr for MacroStep {
+    type Err = eyre::Error;
+
+    fn from_str(value: &str) -> Result<Self> {
+        let mut parts = value.trim().splitn(3, ':');
+        let verb = parts.next().unwrap_or_default();
+        let first = parts.next().unwrap_or_default();
+        let rest = parts.next();
+        match (verb, rest) {
+            ("advance", None) => Ok(MacroStep::Advance(slot_from_name(first)?)),
+            ("equip", Some(spec)) if !spec.is_empty() => {
+                Ok(MacroStep::Equip(slot_from_name(first)?, spec.to_string()))
+            }
+            ("use", spec) if !first.is_empty() => {
+                // Form specs don't hold colons, but be forgiving.
+                let spec = match spec {
+                    Some(more) => format!("{first}:{more}"),
+                    None => first.to_string(),
+                };
+                Ok(MacroStep::Use(spec))
+            }
+            ("equipset", None) => {
+                let id = first
+                    .parse::<u32>()
+                    .map_err(|_| eyre!("'{first}' is not an equipment set id"))?;
+                Ok(MacroStep::EquipSet(id))
+            }
+            _ => Err(eyre!("'{value}' is not a macro step")),
+        }
+    }
+}
+
+impl Display for MacroStep {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MacroStep::Advance(slot) => write!(f, "advance:{}", slot.to_string().to_lowercase()),
+            MacroStep::Equip(slot, spec) => {
+                write!(f, "equip:{}:{spec}", slot.to_string().to_lowercase())
+            }
+            MacroStep::Use(spec) => write!(f, "use:{spec}"),
+            MacroStep::EquipSet(id) => write!(f, "equipset:{id}"),
+        }
+    }
+}
+
+/// Whether we're recording a macro, and what's left of the one playing.
+#[derive(Debug, Clone, Default)]
+pub struct MacroState {
+    recording: Option<Vec<MacroStep>>,
+    playing: VecDeque<MacroStep>,
+}
+
+impl MacroState {
+    pub fn is_recording(&self) -> bool {
+        self.recording.is_some()
+    }
+
+    pub fn is_playing(&self) -> bool {
+        !self.playing.is_empty()
+    }
+
+    /// Start a fresh recording, dropping any unsaved one.
+    pub fn start_recording(&mut self) {
+        self.recording = Some(Vec::new());
+    }
+
+    /// Stop recording and hand back the steps, if we were recording.
+    pub fn finish_recording(&mut self) -> Option<Vec<MacroStep>> {
+        self.recording.take()
+    }
+
+    /// Note a step if we're recording. Steps a playing macro takes are its
+    /// own, so they aren't recorded again.
+    pub fn record(&mut self, step: MacroStep) {
+        if self.is_playing() {
+            return;
+        }
+        if let Some(steps) = self.recording.as_mut() {
+            if steps.len() < MAX_MACRO_STEPS {
+                steps.push(step);
+            } else {
+                log::debug!("Macro is full; not recording {step}.");
+            }
+        }
+    }
+
+    pub fn start_playback(&mut self, steps: Vec<MacroStep>) {
+        self.playing = steps.into();
+    }
+
+    /// The step of the playing macro to take now. It stays queued, so the
+    /// macro counts as playing while the step runs; call `step_done()` after.
+    pub fn next_step(&self) -> Option<MacroStep> {
+        self.playing.front().cloned()
+    }
+
+    /// The step from `next_step()` has run; move on to the one after it.
+    pub fn step_done(&mut self) {
+        self.playing.pop_front();
+    }
+
+    /// Drop the rest of the playing macro. Returns true if one was playing.
+    pub fn stop_playback(&mut self) -> bool {
+        let was_playing = self.is_playing();
+        self.playing.clear();
+        was_playing
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn steps_round_trip() {
+        let steps = vec![
+            MacroStep::Advance(CycleSlot::Left),
+            MacroStep::Equip(CycleSlot::Right, "Skyrim.esm|0x12eb7".to_string()),
+            MacroStep::Use("health_proxy".to_string()),
+            MacroStep::EquipSet(3),
+        ];
+        for step in steps {
+            let text = step.to_string();
+            assert_eq!(text.parse::<MacroStep>().expect("steps parse"), step);
+        }
+        assert_eq!(
+            "equip:left:Skyrim.esm|0x12eb7"
+                .parse::<MacroStep>()
+                .expect("equips parse"),
+            MacroStep::Equip(CycleSlot::Left, "Skyrim.esm|0x12eb7".to_string())
+        );
+
+        assert!("advance:feet".parse::<MacroStep>().is_err());
+        assert!("equip:left".parse::<MacroStep>().is_err());
+        assert!("equipset:first".parse::<MacroStep>().is_err());
+        assert!("dance".parse::<MacroStep>().is_err());
+    }
+
+    #[test]
+    fn recording_and_playing() {
+        let mut state = MacroState::default();
+        state.record(MacroStep::Advance(CycleSlot::Power));
+        assert!(!state.is_recording());
+
+        state.start_recording();
+        state.record(MacroStep::Advance(CycleSlot::Power));
+        state.record(MacroStep::EquipSet(1));
+        let steps = state.finish_recording().expect("we were recording");
+        assert_eq!(steps.len(), 2);
+        assert!(state.finish_recording().is_none());
+
+        // A playing macro's own steps don't end up in a new recording.
+        state.start_recording();
+        state.start_playback(steps.clone());
+        assert_eq!(
+            state.next_step(),
+            Some(MacroStep::Advance(CycleSlot::Power))
+        );
+        state.record(MacroStep::Advance(CycleSlot::Power));
+        state.step_done();
+        // The last step is still playing while it runs.
+        assert_eq!(state.next_step(), Some(MacroStep::EquipSet(1)));
+        state.record(MacroStep::EquipSet(1));
+        state.step_done();
+        assert!(!state.is_playing());
+        assert_eq!(state.finish_recording(), Some(Vec::new()));
+
+        state.start_recording();
+        state.start_playback(steps.clone());
+        assert!(state.stop_playback());
+        assert!(!state.stop_playback());
+        assert_eq!(state.next_step(), None);
+        state.record(MacroStep::Use("health_proxy".to_string()));
+        assert_eq!(
+            state.finish_recording(),
+            Some(vec![MacroStep::Use("health_proxy".to_string())])
+        );
+
+        state.start_recording();
+        for _ in 0..MAX_MACRO_STEPS + 5 {
+            state.record(MacroStep::Advance(CycleSlot::Left));
+        }
+        assert_eq!(
+            state.finish_recording().map(|xs| xs.len()),
+            Some(MAX_MACRO_STEPS)
+        );
+    }
+}
@@ -7,14 +7,25 @@
 //!
 //! There is little defined in this module file, but everything it re-exports
 //! is available to be bridged to C++ in the `plugin` module.
+pub mod autofill;
+pub mod chords;
+pub mod conflicts;
 pub mod control;
 pub mod cycleentries;
 pub mod cycles;
 pub mod facade;
+pub mod filters;
+pub mod history;
 pub mod keys;
+pub mod loadout;
 pub mod logs;
+pub mod macros;
+pub mod quickslots;
 pub mod settings;
 pub mod strings;
+pub mod triggers;
+pub mod validation;
+pub mod watcher;
 
 pub use facade::*;
 pub use logs::*;
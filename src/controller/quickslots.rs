@@ -0,0 +1,101 @@
+//! Quickslots: hotkeys that use or equip one item right away.
+//!
+//! A quickslot never moves a cycle. Pressing it equips or consumes its item
+//! directly and shows the result in the matching HUD slot. Quickslot keys
+//! live in the `[Quickslots]` section of the settings file. The key is the
+//! key code, the same numbers the hotkey settings use, and the value is what
+//! the key is bound to:
+//!
+//! ```ini
+//! [Quickslots]
+//! 59 = Skyrim.esm|0x3eadd
+//! 60 = utility:0
+//! 61 =
+//! ```
+//!
+//! A value is either a form spec or a cycle name and a position in that
+//! cycle, counting from zero. A key with no value does nothing until the
+//! player binds it in game by pressing it while hovering over an item in a
+//! menu. Bindings made in game are saved with the character and win over the
+//! settings file.
+
+use std::fmt::Display;
+use std::str::FromStr;
+
+use eyre::{eyre, Result};
+
+use super::keys::CycleSlot;
+
+/// What a quickslot key uses when pressed.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum QuickslotTarget {
+    /// One specific item or spell, by form spec.
+    Item(String),
+    /// Whatever sits at this position in a cycle right now.
+    Entry(CycleSlot, usize),
+}
+
+impl FromStr for QuickslotTarget {
+    type Err = eyre::Error;
+
+    fn from_str(value: &str) -> Result<Self> {
+        let value = value.trim();
+        if value.is_empty() {
+            return Err(eyre!("a quickslot needs a form spec or a cycle position"));
+        }
+        let Some((name, index)) = value.split_once(':') else {
+            return Ok(QuickslotTarget::Item(value.to_string()));
+        };
+        let Ok(slot) = name.trim().parse::<CycleSlot>() else {
+            return Err(eyre!("'{name}' is not a cycle; quickslot='{value}'"));
+        };
+        let Ok(index) = index.trim().parse::<usize>() else {
+            return Err(eyre!(
+                "'{index}' is not a cycle position; quickslot='{value}'"
+            ));
+        };
+        Ok(QuickslotTarget::Entry(slot, index))
+    }
+}
+
+impl Display for QuickslotTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            QuickslotTarget::Item(spec) => write!(f, "{spec}"),
+            QuickslotTarget::Entry(slot, index) => {
+                write!(f, "{}:{index}", slot.to_string().to_lowercase())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parsing_targets() {
+        let item: QuickslotTarget = "Skyrim.esm|0x3eadd".parse().expect("form specs parse");
+        assert_eq!(
+            item,
+            QuickslotTarget::Item("Skyrim.esm|0x3eadd".to_string())
+        );
+        let proxy: QuickslotTarget = " health_proxy ".parse().expect("proxies parse");
+        assert_eq!(proxy, QuickslotTarget::Item("health_proxy".to_string()));
+
+        let entry: QuickslotTarget = "Utility: 2".parse().expect("cycle positions parse");
+        assert_eq!(entry, QuickslotTarget::Entry(CycleSlot::Utility, 2));
+        assert_eq!(entry.to_string(), "utility:2");
+        assert_eq!(
+            entry
+                .to_string()
+                .parse::<QuickslotTarget>()
+                .expect("targets round-trip"),
+            entry
+        );
+
+        assert!("".parse::<QuickslotTarget>().is_err());
+        assert!("feet:1".parse::<QuickslotTarget>().is_err());
+        assert!("left:first".parse::<QuickslotTarget>().is_err());
+    }
+}
@@ -2,21 +2,37 @@
 //! interface for all settings data to C++ and Rust both. Does some data
 //! validation and some translation from older versions, but this file is
 //! otherwise all fairly predictable.
+//!
+//! A character may have an override file in `SoulsyHUD/characters`, named
+//! for the character, e.g. `Lydia.ini`. It uses the same sections and keys as
+//! the main file. Only the keys it sets change; everything else comes from
+//! the main file.
 
-use std::{path::Path, sync::Mutex};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
 
 use eyre::Result;
 use ini::Ini;
 use log::Level;
 use once_cell::sync::Lazy;
+use strfmt::strfmt;
 use strum::Display;
 
-use super::keys::Hotkey;
-use crate::{layouts::shared::NamedAnchor, plugin::HudElement};
+use super::chords::KeyBinding;
+use super::conflicts::{find_conflicts, log_conflicts, HotkeyConflict, KeyMap};
+use super::control::{notify, translated_key, RequestedAction};
+use super::keys::{Hotkey, Modifier};
+use super::validation::SettingsReport;
+use crate::layouts::shared::NamedAnchor;
+use crate::plugin::{playerName, HudElement};
 
 /// This is the path to players's modified settings.
 static SETTINGS_PATH: &str = "./data/MCM/Settings/SoulsyHUD.ini";
 
+/// Per-character override files live here, named for the character.
+static CHARACTER_SETTINGS_DIR: &str = "./data/SKSE/Plugins/SoulsyHUD/characters";
+
 /// This is the path to the mod settings definition file.
 /// static INI_PATH: &str = "./data/MCM/Config/SoulsyHUD/settings.ini";
 
@@ -31,6 +47,60 @@ pub fn settings() -> UserSettings {
     settings.clone()
 }
 
+const FMT_SETTINGS_PROBLEMS: &str = "$SoulsyHUD_fmt_SettingsProblems";
+
+/// What we found wrong with the settings the last time we read them. Kept
+/// apart from `SETTINGS` so the clone in `settings()` stays cheap.
+static CHECKS: Lazy<Mutex<SettingsChecks>> = Lazy::new(|| Mutex::new(SettingsChecks::default()));
+
+pub fn settings_checks() -> SettingsChecks {
+    let checks = CHECKS
+        .lock()
+        .expect("Unrecoverable runtime problem: cannot acquire settings checks lock.");
+    checks.clone()
+}
+
+/// Problems in the settings file and key bindings that get in each other's way.
+#[derive(Debug, Clone, Default)]
+pub struct SettingsChecks {
+    validation: SettingsReport,
+    conflicts: Vec<HotkeyConflict>,
+}
+
+impl SettingsChecks {
+    /// Problems found in the settings file.
+    pub fn validation(&self) -> &SettingsReport {
+        &self.validation
+    }
+
+    /// Key bindings that get in each other's way.
+    pub fn hotkey_conflicts(&self) -> &[HotkeyConflict] {
+        &self.conflicts
+    }
+
+    /// One line per problem and conflict, for Papyrus.
+    pub fn lines(&self) -> Vec<String> {
+        let mut lines = self.validation.lines();
+        lines.extend(self.conflicts.iter().map(|xs| xs.to_string()));
+        lines
+    }
+
+    /// Log everything we found and tell the player how much there was, once.
+    pub fn report(&self) {
+        self.validation.log();
+        log_conflicts(&self.conflicts);
+        let count = self.validation.problems().len() + self.conflicts.len();
+        if count == 0 {
+            return;
+        }
+        let mut vars = HashMap::new();
+        vars.insert("count".to_string(), count.to_string());
+        if let Ok(message) = strfmt(&translated_key(FMT_SETTINGS_PROBLEMS), &vars) {
+            notify(&message);
+        }
+    }
+}
+
 /// Wrapper for C++ convenience; logs errors but does no more
 pub fn refresh_user_settings() {
     match UserSettings::refresh() {
@@ -64,6 +134,14 @@ pub struct UserSettings {
     right: u32,
     /// The key for equip sets. iEquipSetCycleKey
     equipset: i32,
+    /// An optional hotkey to swap back to the previous equip set. iEquipSetSwapKey
+    equipset_swap_key: i32,
+    /// Long-pressing the equip set key swaps back to the previous set. bLongPressSwapsEquipSet
+    long_press_swaps_equipset: bool,
+    /// What a long press of each hotkey does, for hotkeys with an explicit
+    /// binding. sLongPressPower, sLongPressUtility, sLongPressLeft,
+    /// sLongPressRight, sLongPressEquipSet
+    long_press_bindings: Vec<(Hotkey, RequestedAction)>,
 
     /// How the player wants to use the utility item. uHowToActivate
     how_to_activate: ActivationMethod,
@@ -78,6 +156,8 @@ pub struct UserSettings {
     how_to_cycle: ActivationMethod,
     /// An optional modifier key for all cycle hotkeys. E.g., shift + key. iCycleModifierKey
     cycle_modifier: i32,
+    /// An optional modifier key that makes cycle hotkeys move backward. iReverseModifierKey
+    reverse_modifier: i32,
 
     /// How the player adds and removes items in menus. uHowTriggerInMenus
     how_to_toggle: ActivationMethod,
@@ -93,6 +173,13 @@ pub struct UserSettings {
     /// An optional dedicated hotkey for unequipping both hands. iUnequipHotkey
     unequip_hotkey: i32,
 
+    /// An optional hotkey to undo the last cycle edit. iUndoKey
+    undo_key: i32,
+    /// An optional hotkey to redo the last undone cycle edit. iRedoKey
+    redo_key: i32,
+    /// An optional hotkey to start or cancel recording a macro. iMacroRecordKey
+    macro_record_key: i32,
+
     /// Matching left and right hands. bLongPressMatches
     long_press_matches: bool,
 
@@ -109,6 +196,24 @@ pub struct UserSettings {
     equip_delay_ms: u32,
     /// The number of milliseconds it takes for a press to be a long one.
     long_press_ms: u32,
+    /// The longest gap between taps in a key sequence, in milliseconds. uKeySequenceMillis
+    key_sequence_ms: u32,
+    /// The longest gap between the two presses of a double tap, in milliseconds. uDoubleTapMillis
+    double_tap_ms: u32,
+    /// How long a playing macro waits between steps, in milliseconds. uMacroStepMillis
+    macro_step_ms: u32,
+    /// Reload settings and layout when their files change on disk. bWatchFiles
+    watch_files: bool,
+    /// How long watched files must sit unchanged before we reload, in milliseconds. uWatchDebounceMillis
+    watch_debounce_ms: u32,
+    /// The number of milliseconds of cycling inactivity before a cycle snaps back to its pinned entry.
+    pin_timeout_ms: u32,
+    /// The number of milliseconds after a triggered equipset fires before any can fire again.
+    trigger_cooldown_ms: u32,
+    /// The most entries any one cycle may hold. Min 1, max 100. uMaxCycleLength
+    maxlen: u32,
+    /// What to do when the player adds to a full cycle. uCycleOverflow
+    overflow_policy: OverflowPolicy,
     /// Whether to fade out hud when not in combat.
     autofade: bool,
     /// The time in milliseconds it takes to fade out.
@@ -127,15 +232,35 @@ pub struct UserSettings {
     controller_kind: u32, // 0 = pc, 1 = ps, 2 = xbox
     /// True if the player wants us to cycle through ammo.
     cycle_ammo: bool,
+    /// Skip utility items the player has run out of when advancing. bSkipEmptyUtilities
+    skip_empty_utilities: bool,
+    /// Skip spells the player lacks the magicka to cast when advancing. bSkipUnaffordableSpells
+    skip_unaffordable_spells: bool,
+    /// Skip items the player no longer has when advancing. bSkipLostItems
+    skip_lost_items: bool,
     /// True if icons should be drawn in living color.
     colorize_icons: bool,
     /// Equip sets unequip. bEquipSetsUnequip
     equip_sets_unequip: bool,
     /// The identifier for the mod in SKSE cosaves. Defaults to SOLS.
     skse_identifier: String,
+    /// Names the cycle template new characters start with, if set. sTemplateTag
+    template_tag: String,
+
+    /// Auto-fill rules as (name, rule text) pairs, from the AutoFill section.
+    auto_fill_rules: Vec<(String, String)>,
+    /// Key chords and sequences as (action, keys) pairs, from the Chords section.
+    key_chords: Vec<(String, String)>,
+    /// Quickslot keys and what they're bound to, from the Quickslots section.
+    /// An empty target means the player binds the key in game.
+    quickslots: Vec<(u32, String)>,
+    /// Macro keys and their names, from the Macros section.
+    macro_keys: Vec<(u32, String)>,
 
     /// Settings we need from DisplayTweaks, if it exists
     display_tweaks: DisplayTweaks,
+    /// The character whose override file was layered over the main file, if any.
+    character_overrides: Option<String>,
 }
 
 impl Default for UserSettings {
@@ -149,6 +274,9 @@ impl Default for UserSettings {
             utility: 6,
             right: 7,
             equipset: 9,
+            equipset_swap_key: -1,
+            long_press_swaps_equipset: false,
+            long_press_bindings: Vec::new(),
             refresh_layout: 8,
             anchor_loc: NamedAnchor::None,
             scale_override: 0.0,
@@ -158,6 +286,7 @@ impl Default for UserSettings {
             group_potions: false,
             how_to_cycle: ActivationMethod::Hotkey,
             cycle_modifier: -1,
+            reverse_modifier: -1,
             long_press_matches: false,
             how_to_toggle: ActivationMethod::Hotkey,
             menu_modifier: -1,
@@ -165,8 +294,20 @@ impl Default for UserSettings {
             unarmed_handling: UnarmedMethod::None,
             unequip_modifier: -1,
             unequip_hotkey: -1,
-            equip_delay_ms: 750, // in milliseconds
-            long_press_ms: 1250, // in milliseconds
+            undo_key: -1,
+            redo_key: -1,
+            macro_record_key: -1,
+            equip_delay_ms: 750,  // in milliseconds
+            long_press_ms: 1250,  // in milliseconds
+            key_sequence_ms: 500, // in milliseconds
+            double_tap_ms: 300,   // in milliseconds
+            macro_step_ms: 500,   // in milliseconds
+            watch_files: false,
+            watch_debounce_ms: 500,     // in milliseconds
+            pin_timeout_ms: 5000,       // in milliseconds
+            trigger_cooldown_ms: 10000, // in milliseconds
+            maxlen: 20,
+            overflow_policy: OverflowPolicy::Reject,
             autofade: true,
             max_alpha: 1.0,
             min_alpha: 0.0,
@@ -175,10 +316,19 @@ impl Default for UserSettings {
             cycling_slows_time: false,
             slow_time_factor: 0.25,
             cycle_ammo: true,
+            skip_empty_utilities: true,
+            skip_unaffordable_spells: false,
+            skip_lost_items: true,
             colorize_icons: true,
             equip_sets_unequip: true,
             skse_identifier: "SOLS".to_string(),
+            template_tag: String::new(),
+            auto_fill_rules: Vec::new(),
+            key_chords: Vec::new(),
+            quickslots: Vec::new(),
+            macro_keys: Vec::new(),
             display_tweaks: DisplayTweaks::default(),
+            character_overrides: None,
         }
     }
 }
@@ -190,24 +340,71 @@ impl UserSettings {
         s
     }
 
+    /// Re-read the MCM-controlled file, with the current character's
+    /// overrides layered over it if they have any.
     pub fn refresh() -> Result<()> {
+        let conf = Ini::load_from_file(SETTINGS_PATH)?;
+        let character = playerName();
+        let overrides = character_settings_path(&character).and_then(|path| {
+            if !path.exists() {
+                return None;
+            }
+            match Ini::load_from_file(&path) {
+                Ok(overrides) => {
+                    log::info!("Reading settings overrides from {}", path.display());
+                    Some(overrides)
+                }
+                Err(e) => {
+                    log::warn!("Ignoring settings overrides in {}; {e:#}", path.display());
+                    None
+                }
+            }
+        });
+        let fresh = UserSettings::from_layered(conf, &character, overrides.as_ref())?;
+
         let mut settings = SETTINGS
             .lock()
             .expect("Unrecoverable runtime problem: cannot acquire settings lock.");
-        settings.read_from_file(SETTINGS_PATH)
+        *settings = fresh;
+        Ok(())
     }
 
     pub fn refresh_with(fpath: &str) -> Result<()> {
+        let mut fresh = UserSettings::default();
+        fresh.read_from_file(fpath)?;
         let mut settings = SETTINGS
             .lock()
             .expect("Unrecoverable runtime problem: cannot acquire settings lock.");
-        settings.read_from_file(fpath)
+        *settings = fresh;
+        Ok(())
+    }
+
+    /// Read settings from scratch, with this character's overrides layered
+    /// over the main file if they have any. Starting from the defaults means
+    /// nothing another character's overrides set can linger.
+    fn from_layered(mut conf: Ini, character: &str, overrides: Option<&Ini>) -> Result<Self> {
+        let mut settings = UserSettings::default();
+        if let Some(overrides) = overrides {
+            let changed = layer_ini(&mut conf, overrides);
+            log::info!(
+                "Layered settings overrides for {character}: {}",
+                changed.join(", ")
+            );
+            settings.character_overrides = Some(character.to_string());
+        }
+        settings.read_from_conf(&conf)?;
+        Ok(settings)
     }
 
     /// Refresh ourselves from the MCM-controlled file.
     pub fn read_from_file(&mut self, fpath: &str) -> Result<()> {
         // We'll fall back to defaults at a different level.
         let conf = Ini::load_from_file(fpath)?;
+        self.read_from_conf(&conf)
+    }
+
+    /// Refresh ourselves from settings already loaded.
+    fn read_from_conf(&mut self, conf: &Ini) -> Result<()> {
         let empty = ini::Properties::new();
 
         // This is the sound of my brain going clonk.
@@ -236,6 +433,8 @@ impl UserSettings {
         self.utility = read_from_ini(self.utility, "uUtilityCycleKey", controls);
         self.how_to_cycle = read_from_ini(self.how_to_cycle, "uHowToCycle", controls);
         self.cycle_modifier = read_from_ini(self.cycle_modifier, "iCycleModifierKey", controls);
+        self.reverse_modifier =
+            read_from_ini(self.reverse_modifier, "iReverseModifierKey", controls);
         self.long_press_matches =
             read_from_ini(self.long_press_matches, "bLongPressMatches", controls);
 
@@ -258,6 +457,9 @@ impl UserSettings {
         self.unequip_modifier =
             read_from_ini(self.unequip_modifier, "iUnequipModifierKey", controls);
         self.unequip_hotkey = read_from_ini(self.unequip_hotkey, "iUnequipHotkey", controls);
+        self.undo_key = read_from_ini(self.undo_key, "iUndoKey", controls);
+        self.redo_key = read_from_ini(self.redo_key, "iRedoKey", controls);
+        self.macro_record_key = read_from_ini(self.macro_record_key, "iMacroRecordKey", controls);
 
         self.equip_delay_ms = u32::clamp(
             read_from_ini(self.equip_delay_ms, "uEquipDelay", options),
@@ -268,6 +470,21 @@ impl UserSettings {
         if self.long_press_ms < self.equip_delay_ms {
             self.long_press_ms = self.equip_delay_ms + 100;
         }
+        self.key_sequence_ms = read_from_ini(self.key_sequence_ms, "uKeySequenceMillis", options);
+        self.double_tap_ms = read_from_ini(self.double_tap_ms, "uDoubleTapMillis", options);
+        self.macro_step_ms = read_from_ini(self.macro_step_ms, "uMacroStepMillis", options);
+        self.watch_files = read_from_ini(self.watch_files, "bWatchFiles", options);
+        self.watch_debounce_ms =
+            read_from_ini(self.watch_debounce_ms, "uWatchDebounceMillis", options);
+        self.pin_timeout_ms = read_from_ini(self.pin_timeout_ms, "uPinnedReturnMillis", options);
+        self.trigger_cooldown_ms =
+            read_from_ini(self.trigger_cooldown_ms, "uTriggerCooldownMillis", options);
+        self.maxlen = u32::clamp(
+            read_from_ini(self.maxlen, "uMaxCycleLength", options),
+            1,
+            100,
+        );
+        self.overflow_policy = read_from_ini(self.overflow_policy, "uCycleOverflow", options);
 
         self.autofade = read_from_ini(self.autofade, "bAutoFade", options);
         self.fade_time = read_from_ini(self.fade_time, "uFadeTime", options);
@@ -286,19 +503,222 @@ impl UserSettings {
         self.slow_time_factor = percentage as f32 / 100.0;
 
         self.cycle_ammo = read_from_ini(self.cycle_ammo, "bCycleAmmo", options);
+        self.skip_empty_utilities =
+            read_from_ini(self.skip_empty_utilities, "bSkipEmptyUtilities", options);
+        self.skip_unaffordable_spells = read_from_ini(
+            self.skip_unaffordable_spells,
+            "bSkipUnaffordableSpells",
+            options,
+        );
+        self.skip_lost_items = read_from_ini(self.skip_lost_items, "bSkipLostItems", options);
         self.colorize_icons = read_from_ini(self.colorize_icons, "bColorizeIcons", options);
         self.skse_identifier =
             read_from_ini(self.skse_identifier.clone(), "sSKSEIdentifier", options);
+        self.template_tag = read_from_ini(self.template_tag.clone(), "sTemplateTag", options);
 
         self.equipset = read_from_ini(self.equipset, "iEquipSetCycleKey", controls);
+        self.equipset_swap_key =
+            read_from_ini(self.equipset_swap_key, "iEquipSetSwapKey", controls);
+        self.long_press_swaps_equipset = read_from_ini(
+            self.long_press_swaps_equipset,
+            "bLongPressSwapsEquipSet",
+            controls,
+        );
+        self.long_press_bindings = [
+            (Hotkey::Power, "sLongPressPower"),
+            (Hotkey::Utility, "sLongPressUtility"),
+            (Hotkey::Left, "sLongPressLeft"),
+            (Hotkey::Right, "sLongPressRight"),
+            (Hotkey::Equipment, "sLongPressEquipSet"),
+        ]
+        .into_iter()
+        .filter_map(|(hotkey, key)| {
+            let text = read_from_ini(String::new(), key, controls);
+            if text.trim().is_empty() {
+                return None;
+            }
+            match text.parse::<RequestedAction>() {
+                Ok(action) => Some((hotkey, action)),
+                Err(e) => {
+                    log::warn!("Ignoring {key}; {e:#}");
+                    None
+                }
+            }
+        })
+        .collect();
         self.equip_sets_unequip =
             read_from_ini(self.equip_sets_unequip, "bEquipSetsUnequip", options);
 
+        self.auto_fill_rules = if let Some(s) = conf.section(Some("AutoFill")) {
+            s.iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        self.key_chords = if let Some(s) = conf.section(Some("Chords")) {
+            s.iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        self.quickslots = if let Some(s) = conf.section(Some("Quickslots")) {
+            s.iter()
+                .filter_map(|(k, v)| match k.trim().parse::<u32>() {
+                    Ok(key) => Some((key, v.trim().to_string())),
+                    Err(_) => {
+                        log::warn!("Ignoring quickslot '{k}'; quickslots are named by key code.");
+                        None
+                    }
+                })
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        self.macro_keys = if let Some(s) = conf.section(Some("Macros")) {
+            s.iter()
+                .filter_map(|(k, v)| match k.trim().parse::<u32>() {
+                    Ok(key) => Some((key, v.trim().to_string())),
+                    Err(_) => {
+                        log::warn!("Ignoring macro '{k}'; macros are named by key code.");
+                        None
+                    }
+                })
+                .collect()
+        } else {
+            Vec::new()
+        };
+
         self.display_tweaks.read_ini();
+        let checks = SettingsChecks {
+            validation: SettingsReport::check(conf),
+            conflicts: find_conflicts(&self.key_map()),
+        };
+        *CHECKS
+            .lock()
+            .expect("Unrecoverable runtime problem: cannot acquire settings checks lock.") = checks;
 
         Ok(())
     }
 
+    /// Every key binding, gathered for conflict analysis. Hotkeys are listed
+    /// in the order `Hotkey::from()` checks them.
+    pub fn key_map(&self) -> KeyMap {
+        // Negative codes mean unbound, so they come through as zero.
+        let optional = |code: i32| code.max(0) as u32;
+        let mut hotkeys: Vec<(String, u32)> = [
+            ("uPowerCycleKey", self.power),
+            ("uUtilityCycleKey", self.utility),
+            ("uLeftCycleKey", self.left),
+            ("uRightCycleKey", self.right),
+            ("iEquipSetCycleKey", optional(self.equipset)),
+            ("uRefreshKey", self.refresh_layout),
+            ("uShowHideKey", self.showhide),
+            ("uUtilityActivateKey", self.activate),
+            ("iUnequipHotkey", optional(self.unequip_hotkey)),
+            ("iUndoKey", optional(self.undo_key)),
+            ("iRedoKey", optional(self.redo_key)),
+            ("iEquipSetSwapKey", optional(self.equipset_swap_key)),
+            ("iMacroRecordKey", optional(self.macro_record_key)),
+        ]
+        .into_iter()
+        .map(|(name, code)| (name.to_string(), code))
+        .collect();
+        hotkeys.extend(
+            self.quickslots
+                .iter()
+                .map(|(code, _)| (format!("quickslot {code}"), *code)),
+        );
+        hotkeys.extend(
+            self.macro_keys
+                .iter()
+                .map(|(code, _)| (format!("macro {code}"), *code)),
+        );
+        hotkeys.retain(|(_, code)| *code > 0);
+
+        let modifiers = [
+            (
+                Modifier::Activate,
+                "iUtilityActivateModifier",
+                self.activate_modifier,
+            ),
+            (Modifier::Cycle, "iCycleModifierKey", self.cycle_modifier),
+            (
+                Modifier::Unequip,
+                "iUnequipModifierKey",
+                self.unequip_modifier,
+            ),
+            (Modifier::Menu, "iMenuModifierKey", self.menu_modifier),
+            (
+                Modifier::Reverse,
+                "iReverseModifierKey",
+                self.reverse_modifier,
+            ),
+        ]
+        .into_iter()
+        .filter(|(_, _, code)| code.is_positive())
+        .map(|(modifier, name, code)| (modifier, name.to_string(), code.unsigned_abs()))
+        .collect();
+
+        // These follow the fallbacks in `Hotkey::long_press_action()`.
+        let advance = matches!(self.how_to_cycle, ActivationMethod::LongPress);
+        let unequip = matches!(self.unarmed_handling, UnarmedMethod::LongPress);
+        let consume = matches!(self.how_to_activate, ActivationMethod::LongPress);
+        let uses = |choices: &[(bool, &str)]| -> Vec<String> {
+            choices
+                .iter()
+                .filter(|(on, _)| *on)
+                .map(|(_, name)| name.to_string())
+                .collect()
+        };
+        let hands = [
+            (unequip, "unequip"),
+            (self.long_press_matches, "dual-wield"),
+            (advance, "advance"),
+        ];
+        let long_presses = [
+            (
+                Hotkey::Power,
+                "uPowerCycleKey",
+                uses(&[(unequip, "unequip"), (advance, "advance")]),
+            ),
+            (
+                Hotkey::Utility,
+                "uUtilityCycleKey",
+                uses(&[(consume, "use the item"), (advance, "advance")]),
+            ),
+            (Hotkey::Left, "uLeftCycleKey", uses(&hands)),
+            (Hotkey::Right, "uRightCycleKey", uses(&hands)),
+        ]
+        .into_iter()
+        .filter(|(hotkey, _, _)| self.long_press_binding(hotkey).is_none())
+        .map(|(_, name, uses)| (name.to_string(), uses))
+        .collect();
+
+        // Bindings that don't parse are logged when the chords are applied.
+        let chords = self
+            .key_chords
+            .iter()
+            .filter_map(|(name, value)| KeyBinding::parse(name, value).ok())
+            .collect();
+
+        KeyMap {
+            hotkeys,
+            modifiers,
+            long_presses,
+            chords,
+        }
+    }
+
+    /// The character whose overrides are in effect, if any.
+    pub fn character_overrides(&self) -> Option<&str> {
+        self.character_overrides.as_deref()
+    }
+
     pub fn log_level(&self) -> Level {
         self.log_level
     }
@@ -327,6 +747,16 @@ impl UserSettings {
             -1
         }
     }
+    pub fn undo_key(&self) -> i32 {
+        self.undo_key
+    }
+    pub fn redo_key(&self) -> i32 {
+        self.redo_key
+    }
+
+    pub fn macro_record_key(&self) -> i32 {
+        self.macro_record_key
+    }
 
     pub fn should_start_long_press_timer(&self, key: u32) -> bool {
         let hotkey = Hotkey::from(key);
@@ -349,6 +779,15 @@ impl UserSettings {
         if matches!(self.unarmed_handling, UnarmedMethod::LongPress) && can_be_unequipped {
             return true;
         }
+        if self.long_press_swaps_equipset && matches!(hotkey, Hotkey::Equipment) {
+            return true;
+        }
+        if self
+            .long_press_binding(&hotkey)
+            .is_some_and(|action| !matches!(action, RequestedAction::None))
+        {
+            return true;
+        }
 
         false
     }
@@ -373,6 +812,9 @@ impl UserSettings {
     pub fn cycle_modifier(&self) -> i32 {
         self.cycle_modifier
     }
+    pub fn reverse_modifier(&self) -> i32 {
+        self.reverse_modifier
+    }
 
     pub fn long_press_to_dual_wield(&self) -> bool {
         self.long_press_matches
@@ -405,6 +847,19 @@ impl UserSettings {
     pub fn equipset(&self) -> i32 {
         self.equipset
     }
+    pub fn equipset_swap_key(&self) -> i32 {
+        self.equipset_swap_key
+    }
+    pub fn long_press_swaps_equipset(&self) -> bool {
+        self.long_press_swaps_equipset
+    }
+    /// The explicit long-press binding for this hotkey, if the player set one.
+    pub fn long_press_binding(&self, hotkey: &Hotkey) -> Option<RequestedAction> {
+        self.long_press_bindings
+            .iter()
+            .find(|(bound, _)| bound == hotkey)
+            .map(|(_, action)| action.clone())
+    }
     pub fn equip_sets_unequip(&self) -> bool {
         self.equip_sets_unequip
     }
@@ -434,7 +889,10 @@ impl UserSettings {
         &self.anchor_loc
     }
     pub fn maxlen(&self) -> u32 {
-        20
+        self.maxlen
+    }
+    pub fn overflow_policy(&self) -> OverflowPolicy {
+        self.overflow_policy
     }
     pub fn equip_delay_ms(&self) -> u32 {
         self.equip_delay_ms
@@ -442,6 +900,40 @@ impl UserSettings {
     pub fn long_press_ms(&self) -> u32 {
         self.long_press_ms
     }
+    pub fn key_sequence_ms(&self) -> u32 {
+        self.key_sequence_ms
+    }
+    pub fn double_tap_ms(&self) -> u32 {
+        self.double_tap_ms
+    }
+    pub fn macro_step_ms(&self) -> u32 {
+        self.macro_step_ms
+    }
+    pub fn watch_files(&self) -> bool {
+        self.watch_files
+    }
+    pub fn watch_debounce_ms(&self) -> u32 {
+        self.watch_debounce_ms
+    }
+    /// The files these settings came from: the main file, plus the
+    /// character's overrides if they're in effect.
+    pub fn source_files(&self) -> Vec<PathBuf> {
+        let mut files = vec![PathBuf::from(SETTINGS_PATH)];
+        if let Some(path) = self
+            .character_overrides
+            .as_deref()
+            .and_then(character_settings_path)
+        {
+            files.push(path);
+        }
+        files
+    }
+    pub fn pin_timeout_ms(&self) -> u32 {
+        self.pin_timeout_ms
+    }
+    pub fn trigger_cooldown_ms(&self) -> u32 {
+        self.trigger_cooldown_ms
+    }
     pub fn autofade(&self) -> bool {
         self.autofade
     }
@@ -468,6 +960,55 @@ impl UserSettings {
         self.cycle_ammo
     }
 
+    pub fn skip_empty_utilities(&self) -> bool {
+        self.skip_empty_utilities
+    }
+
+    pub fn skip_unaffordable_spells(&self) -> bool {
+        self.skip_unaffordable_spells
+    }
+
+    pub fn skip_lost_items(&self) -> bool {
+        self.skip_lost_items
+    }
+
+    pub fn auto_fill_rules(&self) -> Vec<(String, String)> {
+        self.auto_fill_rules.clone()
+    }
+
+    pub fn key_chords(&self) -> Vec<(String, String)> {
+        self.key_chords.clone()
+    }
+
+    pub fn is_quickslot_key(&self, key: u32) -> bool {
+        self.quickslots.iter().any(|(bound, _)| *bound == key)
+    }
+
+    pub fn is_macro_key(&self, key: u32) -> bool {
+        self.macro_keys.iter().any(|(bound, _)| *bound == key)
+    }
+
+    /// The name to show for the macro on this key. Falls back to the key code.
+    pub fn macro_name(&self, key: u32) -> String {
+        self.macro_keys
+            .iter()
+            .find(|(bound, name)| *bound == key && !name.is_empty())
+            .map(|(_, name)| name.clone())
+            .unwrap_or_else(|| key.to_string())
+    }
+
+    /// What the settings file binds this quickslot key to, if anything.
+    pub fn quickslot(&self, key: u32) -> Option<String> {
+        self.quickslots
+            .iter()
+            .find(|(bound, target)| *bound == key && !target.is_empty())
+            .map(|(_, target)| target.clone())
+    }
+
+    pub fn template_tag(&self) -> String {
+        self.template_tag.clone()
+    }
+
     pub fn colorize_icons(&self) -> bool {
         self.colorize_icons
     }
@@ -509,6 +1050,34 @@ impl UserSettings {
     }
 }
 
+/// The override file for a character. Names may only use letters, numbers,
+/// spaces, dashes, and underscores, so the path can't escape its directory.
+fn character_settings_path(name: &str) -> Option<PathBuf> {
+    let name = name.trim();
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|xs| xs.is_alphanumeric() || matches!(xs, ' ' | '-' | '_'));
+    if !valid {
+        log::debug!("'{name}' can't name a settings override file.");
+        return None;
+    }
+    Some(Path::new(CHARACTER_SETTINGS_DIR).join(format!("{name}.ini")))
+}
+
+/// Copy every key the overrides set onto the base settings. Returns the keys
+/// changed, as `Section.key`.
+fn layer_ini(base: &mut Ini, overrides: &Ini) -> Vec<String> {
+    let mut changed = Vec::new();
+    for (section, properties) in overrides.iter() {
+        for (key, value) in properties.iter() {
+            base.with_section(section).set(key, value);
+            changed.push(format!("{}.{key}", section.unwrap_or_default()));
+        }
+    }
+    changed
+}
+
 /// Generic for reading a typed value from the ini structure.
 fn read_from_ini<T: FromIniStr>(default: T, key: &str, section: &ini::Properties) -> T {
     if let Some(str_val) = section.get(key) {
@@ -538,6 +1107,8 @@ pub enum ActivationMethod {
     LongPress,
     /// Use a modifier plus the hotkey.
     Modifier,
+    /// Tap the hotkey twice in quick succession.
+    DoubleTap,
 }
 
 impl FromIniStr for ActivationMethod {
@@ -546,6 +1117,7 @@ impl FromIniStr for ActivationMethod {
             "0" => Some(ActivationMethod::Hotkey),
             "1" => Some(ActivationMethod::LongPress),
             "2" => Some(ActivationMethod::Modifier),
+            "3" => Some(ActivationMethod::DoubleTap),
             _ => None,
         }
     }
@@ -568,6 +1140,28 @@ impl FromIniStr for Level {
     }
 }
 
+/// What to do when the player adds an item to a cycle that's already full.
+#[derive(Debug, Clone, Display, Copy, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    /// Refuse the new item.
+    Reject,
+    /// Make room by removing the entry that was added longest ago.
+    EvictOldest,
+    /// Make room by removing the entry the player has used the least.
+    EvictLeastUsed,
+}
+
+impl FromIniStr for OverflowPolicy {
+    fn from_ini(value: &str) -> Option<Self> {
+        match value {
+            "0" => Some(OverflowPolicy::Reject),
+            "1" => Some(OverflowPolicy::EvictOldest),
+            "2" => Some(OverflowPolicy::EvictLeastUsed),
+            _ => None,
+        }
+    }
+}
+
 /// How the player wants to handle unarmed combat.
 #[derive(Debug, Clone, Display, Copy)]
 pub enum UnarmedMethod {
@@ -581,6 +1175,8 @@ pub enum UnarmedMethod {
     AddToCycles,
     /// Use a dedicated hotkey to switch to bare fists.
     Hotkey,
+    /// Double-tap a cycle key to unequip.
+    DoubleTap,
 }
 
 impl FromIniStr for UnarmedMethod {
@@ -591,6 +1187,7 @@ impl FromIniStr for UnarmedMethod {
             "2" => Some(UnarmedMethod::Modifier),
             "3" => Some(UnarmedMethod::AddToCycles),
             "4" => Some(UnarmedMethod::Hotkey),
+            "5" => Some(UnarmedMethod::DoubleTap),
             _ => None,
         }
     }
@@ -700,6 +1297,9 @@ impl std::fmt::Display for UserSettings {
               left cycle key: {}
              right cycle key: {}
           equipset cycle key: {}
+           equipset swap key: {}
+   long press swaps equipset: {}
+         long_press_bindings: {}
           refresh layout key: {}
       layout anchor override: {}
        layout scale override: {}
@@ -709,6 +1309,7 @@ impl std::fmt::Display for UserSettings {
                group_potions: {}
                 how_to_cycle: {}
               cycle_modifier: {}
+            reverse_modifier: {}
     dual-wield on long press: {}
                how_to_toggle: {}
                menu_modifier: {}
@@ -716,8 +1317,20 @@ impl std::fmt::Display for UserSettings {
             unarmed_handling: {}
             unequip_modifier: {}
               unequip_hotkey: {}
+                    undo_key: {}
+                    redo_key: {}
+            macro_record_key: {}
               equip_delay_ms: {} ms
                long_press_ms: {} ms
+             key_sequence_ms: {} ms
+               double_tap_ms: {} ms
+               macro_step_ms: {} ms
+                 watch_files: {}
+           watch_debounce_ms: {} ms
+              pin_timeout_ms: {} ms
+         trigger_cooldown_ms: {} ms
+                      maxlen: {}
+             overflow_policy: {}
                     autofade: {}
                    fade_time: {} ms
                    max alpha: {}
@@ -726,9 +1339,18 @@ impl std::fmt::Display for UserSettings {
           cycling_slows_time: {}
             slow_time_factor: {} %
                   cycle_ammo: {}
+        skip_empty_utilities: {}
+    skip_unaffordable_spells: {}
+             skip_lost_items: {}
               colorize_icons: {}
           equip_sets_unequip: {}
-             skse_identifier: {}"#,
+             skse_identifier: {}
+                template_tag: {}
+             auto_fill_rules: {}
+                  key_chords: {}
+                  quickslots: {}
+                  macro_keys: {}
+         character_overrides: {}"#,
             self.log_level,
             self.showhide,
             self.power,
@@ -736,6 +1358,13 @@ impl std::fmt::Display for UserSettings {
             self.left,
             self.right,
             self.equipset,
+            self.equipset_swap_key,
+            self.long_press_swaps_equipset,
+            self.long_press_bindings
+                .iter()
+                .map(|(hotkey, action)| format!("{hotkey}={action}"))
+                .collect::<Vec<_>>()
+                .join(", "),
             self.refresh_layout,
             self.anchor_loc,
             self.scale_override,
@@ -745,6 +1374,7 @@ impl std::fmt::Display for UserSettings {
             self.group_potions,
             self.how_to_cycle,
             self.cycle_modifier,
+            self.reverse_modifier,
             self.long_press_matches,
             self.how_to_toggle,
             self.menu_modifier,
@@ -752,8 +1382,20 @@ impl std::fmt::Display for UserSettings {
             self.unarmed_handling,
             self.unequip_modifier,
             self.unequip_hotkey,
+            self.undo_key,
+            self.redo_key,
+            self.macro_record_key,
             self.equip_delay_ms,
             self.long_press_ms,
+            self.key_sequence_ms,
+            self.double_tap_ms,
+            self.macro_step_ms,
+            self.watch_files,
+            self.watch_debounce_ms,
+            self.pin_timeout_ms,
+            self.trigger_cooldown_ms,
+            self.maxlen,
+            self.overflow_policy,
             self.autofade,
             self.fade_time,
             self.max_alpha,
@@ -762,9 +1404,26 @@ impl std::fmt::Display for UserSettings {
             self.cycling_slows_time,
             self.slow_time_factor,
             self.cycle_ammo,
+            self.skip_empty_utilities,
+            self.skip_unaffordable_spells,
+            self.skip_lost_items,
             self.colorize_icons,
             self.equip_sets_unequip,
-            self.skse_identifier
+            self.skse_identifier,
+            self.template_tag,
+            self.auto_fill_rules.len(),
+            self.key_chords.len(),
+            self.quickslots
+                .iter()
+                .map(|(key, target)| format!("{key}={target}"))
+                .collect::<Vec<_>>()
+                .join(", "),
+            self.macro_keys
+                .iter()
+                .map(|(key, name)| format!("{key}={name}"))
+                .collect::<Vec<_>>()
+                .join(", "),
+            self.character_overrides.as_deref().unwrap_or("none")
         )
     }
 }
@@ -869,9 +1528,86 @@ mod tests {
         assert_eq!(missing_field.as_str(), "default");
     }
 
+    #[test]
+    fn parsing_long_press_bindings() {
+        for text in [
+            "advance",
+            "consume",
+            "toggle_hud",
+            "equipset:12",
+            "swap_equipset",
+        ] {
+            let action = text
+                .parse::<RequestedAction>()
+                .expect("this binding should parse");
+            assert_eq!(action.to_string(), text);
+        }
+        assert_eq!(
+            " EquipSet: 3 ".parse::<RequestedAction>().ok(),
+            Some(RequestedAction::EquipSet(3))
+        );
+        assert!("equipset:three".parse::<RequestedAction>().is_err());
+        assert!("dance".parse::<RequestedAction>().is_err());
+
+        let options = UserSettings {
+            long_press_bindings: vec![(Hotkey::Power, RequestedAction::ToggleHud)],
+            ..Default::default()
+        };
+        assert_eq!(
+            options.long_press_binding(&Hotkey::Power),
+            Some(RequestedAction::ToggleHud)
+        );
+        assert_eq!(options.long_press_binding(&Hotkey::Left), None);
+    }
+
     #[test]
     fn can_read_example_ini() {
         let le_options = UserSettings::new_from_file("./tests/fixtures/SoulsyHUD.ini");
         assert!(le_options.long_press_ms > le_options.equip_delay_ms);
     }
+
+    #[test]
+    fn character_overrides_change_only_their_keys() {
+        let mut conf = Ini::load_from_file("./tests/fixtures/test-settings.ini")
+            .expect("ini fixture file should be readable");
+        let overrides =
+            Ini::load_from_str("[Controls]\nuPowerCycleKey = 62\n\n[Macros]\n63 = Dash\n")
+                .expect("overrides should parse");
+        let changed = layer_ini(&mut conf, &overrides);
+        assert_eq!(changed, vec!["Controls.uPowerCycleKey", "Macros.63"]);
+
+        let mut options = UserSettings::default();
+        options
+            .read_from_conf(&conf)
+            .expect("layered settings should be readable");
+        assert_eq!(options.power(), 62);
+        assert_eq!(options.utility(), 6);
+        assert!(options.autofade());
+        assert_eq!(options.macro_name(63), "Dash");
+
+        // Refreshing for one character and then another leaves nothing of
+        // the first character's overrides behind.
+        let main = Ini::load_from_file("./tests/fixtures/test-settings.ini")
+            .expect("ini fixture file should be readable");
+        let plain = UserSettings::from_layered(main.clone(), "Nobody", None)
+            .expect("plain settings should be readable");
+        let lydia = UserSettings::from_layered(main.clone(), "Lydia", Some(&overrides))
+            .expect("Lydia's settings should be readable");
+        assert_eq!(lydia.power(), 62);
+        assert_ne!(plain.power(), 62);
+        assert_eq!(lydia.character_overrides(), Some("Lydia"));
+        let serana_overrides = Ini::load_from_str("[Controls]\nuUtilityCycleKey = 7\n")
+            .expect("overrides should parse");
+        let serana = UserSettings::from_layered(main, "Serana", Some(&serana_overrides))
+            .expect("Serana's settings should be readable");
+        assert_eq!(serana.utility(), 7);
+        assert_eq!(serana.power(), plain.power());
+        assert_eq!(serana.macro_name(63), plain.macro_name(63));
+        assert_eq!(serana.character_overrides(), Some("Serana"));
+        assert_eq!(plain.character_overrides(), None);
+
+        assert!(character_settings_path("Lydia").is_some());
+        assert!(character_settings_path("../SoulsyHUD").is_none());
+        assert!(character_settings_path(" ").is_none());
+    }
 }
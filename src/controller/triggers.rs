@@ -0,0 +1,253 @@
+//! Conditions that equip an equipment set automatically.
+//!
+//! A set can list any number of triggers. When one of them starts to hold
+//! (it didn't at the last check and it does now), the set is equipped. Sets
+//! only fire if the player has switched auto-equip on for them, and no set
+//! fires again until the cooldown has passed.
+//!
+//! Triggers are written as short names, for use in the MCM and in loadouts:
+//! `combat`, `weapons_drawn`, `werewolf`, `vampire_lord`, or
+//! `location:<keyword>`, e.g. `location:LocTypeDungeon`.
+
+use std::fmt::Display;
+use std::time::{Duration, Instant};
+
+use eyre::{eyre, Result};
+
+/// One condition that can equip a set.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub enum Trigger {
+    /// The player enters combat.
+    Combat,
+    /// The player draws their weapons.
+    WeaponsDrawn,
+    /// The player turns into a werewolf.
+    Werewolf,
+    /// The player turns into a vampire lord.
+    VampireLord,
+    /// The player enters a location with this keyword, e.g. `LocTypeDungeon`.
+    Location(String),
+}
+
+impl Trigger {
+    pub fn parse(value: &str) -> Result<Self> {
+        let value = value.trim();
+        if let Some((kind, keyword)) = value.split_once(':') {
+            if kind.eq_ignore_ascii_case("location") && !keyword.trim().is_empty() {
+                return Ok(Trigger::Location(keyword.trim().to_string()));
+            }
+            return Err(eyre!("unknown trigger '{value}'"));
+        }
+        match value.to_lowercase().as_str() {
+            "combat" => Ok(Trigger::Combat),
+            "weapons_drawn" => Ok(Trigger::WeaponsDrawn),
+            "werewolf" => Ok(Trigger::Werewolf),
+            "vampire_lord" => Ok(Trigger::VampireLord),
+            _ => Err(eyre!("unknown trigger '{value}'")),
+        }
+    }
+
+    /// Parse a list of triggers separated by commas or spaces.
+    pub fn parse_list(value: &str) -> Result<Vec<Self>> {
+        value
+            .split(|xs: char| xs == ',' || xs.is_whitespace())
+            .filter(|xs| !xs.is_empty())
+            .map(Trigger::parse)
+            .collect()
+    }
+
+    /// Does this trigger's condition hold right now?
+    pub fn holds(&self, state: &GameState) -> bool {
+        match self {
+            Trigger::Combat => state.in_combat,
+            Trigger::WeaponsDrawn => state.weapons_drawn,
+            Trigger::Werewolf => state.werewolf,
+            Trigger::VampireLord => state.vampire_lord,
+            Trigger::Location(keyword) => state
+                .location_keywords
+                .iter()
+                .any(|xs| xs.eq_ignore_ascii_case(keyword)),
+        }
+    }
+}
+
+impl Display for Trigger {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Trigger::Combat => write!(f, "combat"),
+            Trigger::WeaponsDrawn => write!(f, "weapons_drawn"),
+            Trigger::Werewolf => write!(f, "werewolf"),
+            Trigger::VampireLord => write!(f, "vampire_lord"),
+            Trigger::Location(keyword) => write!(f, "location:{keyword}"),
+        }
+    }
+}
+
+/// What we know about the player when checking triggers.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GameState {
+    pub in_combat: bool,
+    pub weapons_drawn: bool,
+    pub werewolf: bool,
+    pub vampire_lord: bool,
+    /// Editor ids of the keywords on the current location and its parents.
+    pub location_keywords: Vec<String>,
+}
+
+/// Remembers the last game state and when a set last fired, so triggers fire
+/// once when their condition starts instead of on every check.
+#[derive(Debug, Clone, Default)]
+pub struct TriggerWatch {
+    last_state: Option<GameState>,
+    last_fired: Option<Instant>,
+}
+
+impl TriggerWatch {
+    /// Look at a fresh game state and decide which set to equip, if any.
+    /// Candidates are (set id, triggers) pairs, checked in order; the first
+    /// set with a newly-true trigger wins. The very first state seen only
+    /// sets the baseline, so loading a save mid-fight doesn't fire anything.
+    /// The baseline holds still during the cooldown, so a trigger that starts
+    /// then and is still true afterward fires once the cooldown ends.
+    pub fn check(
+        &mut self,
+        state: GameState,
+        candidates: &[(u32, Vec<Trigger>)],
+        cooldown: Duration,
+        now: Instant,
+    ) -> Option<u32> {
+        let Some(previous) = self.last_state.as_ref() else {
+            self.last_state = Some(state);
+            return None;
+        };
+        if let Some(fired) = self.last_fired {
+            if now.duration_since(fired) < cooldown {
+                return None;
+            }
+        }
+
+        let winner = candidates.iter().find_map(|(id, triggers)| {
+            let started = triggers
+                .iter()
+                .any(|xs| xs.holds(&state) && !xs.holds(previous));
+            started.then_some(*id)
+        });
+        self.last_state = Some(state);
+        let winner = winner?;
+        self.last_fired = Some(now);
+        Some(winner)
+    }
+
+    /// Forget the last state, e.g., when a save is loaded.
+    pub fn reset(&mut self) {
+        self.last_state = None;
+        self.last_fired = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parsing_triggers() {
+        let triggers = Trigger::parse_list("combat, Werewolf location:LocTypeDungeon")
+            .expect("these triggers should parse");
+        assert_eq!(
+            triggers,
+            vec![
+                Trigger::Combat,
+                Trigger::Werewolf,
+                Trigger::Location("LocTypeDungeon".to_string())
+            ]
+        );
+        assert_eq!(triggers[2].to_string(), "location:LocTypeDungeon");
+        assert!(Trigger::parse("sneaking").is_err());
+        assert!(Trigger::parse("location:").is_err());
+        assert!(Trigger::parse_list("").expect("empty is fine").is_empty());
+    }
+
+    #[test]
+    fn triggers_fire_on_change_and_respect_cooldown() {
+        let mut watch = TriggerWatch::default();
+        let candidates = vec![
+            (1, vec![Trigger::Combat]),
+            (2, vec![Trigger::Location("LocTypeDungeon".to_string())]),
+        ];
+        let cooldown = Duration::from_secs(10);
+        let start = Instant::now();
+        let calm = GameState::default();
+        let fighting = GameState {
+            in_combat: true,
+            ..Default::default()
+        };
+
+        // The first look only sets the baseline.
+        assert_eq!(
+            watch.check(fighting.clone(), &candidates, cooldown, start),
+            None
+        );
+        assert_eq!(
+            watch.check(calm.clone(), &candidates, cooldown, start),
+            None
+        );
+        assert_eq!(
+            watch.check(fighting.clone(), &candidates, cooldown, start),
+            Some(1)
+        );
+        // Still fighting: nothing new started.
+        assert_eq!(watch.check(fighting, &candidates, cooldown, start), None);
+
+        let dungeon = GameState {
+            location_keywords: vec!["loctypedungeon".to_string()],
+            ..Default::default()
+        };
+        let soon = start + Duration::from_secs(2);
+        assert_eq!(
+            watch.check(dungeon.clone(), &candidates, cooldown, soon),
+            None
+        );
+        assert_eq!(watch.check(calm, &candidates, cooldown, soon), None);
+        let later = start + Duration::from_secs(11);
+        assert_eq!(watch.check(dungeon, &candidates, cooldown, later), Some(2));
+    }
+
+    #[test]
+    fn triggers_starting_during_cooldown_fire_after_it() {
+        let mut watch = TriggerWatch::default();
+        let candidates = vec![(1, vec![Trigger::Combat]), (2, vec![Trigger::Werewolf])];
+        let cooldown = Duration::from_secs(10);
+        let start = Instant::now();
+        let calm = GameState::default();
+        let fighting = GameState {
+            in_combat: true,
+            ..Default::default()
+        };
+        let beastly = GameState {
+            in_combat: true,
+            werewolf: true,
+            ..Default::default()
+        };
+
+        assert_eq!(watch.check(calm, &candidates, cooldown, start), None);
+        assert_eq!(watch.check(fighting, &candidates, cooldown, start), Some(1));
+        // The change lands inside the cooldown, so nothing fires yet.
+        let soon = start + Duration::from_secs(2);
+        assert_eq!(
+            watch.check(beastly.clone(), &candidates, cooldown, soon),
+            None
+        );
+        // Still a werewolf once the cooldown ends: that's the edge we missed.
+        let later = start + Duration::from_secs(11);
+        assert_eq!(
+            watch.check(beastly.clone(), &candidates, cooldown, later),
+            Some(2)
+        );
+        // And it fires only once.
+        let much_later = start + Duration::from_secs(30);
+        assert_eq!(
+            watch.check(beastly, &candidates, cooldown, much_later),
+            None
+        );
+    }
+}
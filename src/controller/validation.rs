@@ -0,0 +1,379 @@
+//! A check of the settings file for mistakes that reading it skips over.
+//!
+//! `read_from_ini()` falls back to the default for anything it can't parse,
+//! which keeps the HUD running but hides typos. This pass looks at the raw
+//! file instead and notes every unknown key, every value that won't parse or
+//! is out of range. Keys bound more than once are the conflict check's
+//! business. The report from the last read is kept so the log, the player,
+//! and the MCM can all see it.
+
+use std::fmt::Display;
+
+use ini::Ini;
+use log::Level;
+
+use super::control::RequestedAction;
+use super::quickslots::QuickslotTarget;
+
+/// The highest key code the plugin hands us: the last gamepad button.
+const MAX_KEY_CODE: u32 = 281;
+
+/// Sections whose keys are the player's own names or key codes.
+const FREEFORM_SECTIONS: &[&str] = &["AutoFill", "Chords", "Equipsets", "Macros", "Quickslots"];
+
+/// Keys older versions wrote that we no longer read. Harmless, so not reported.
+const RETIRED_KEYS: &[&str] = &["uLongPressTime"];
+
+/// What a valid value looks like for one setting.
+#[derive(Debug, Clone, Copy)]
+enum Rule {
+    /// 0 or 1, or true or false.
+    Flag,
+    /// A key code for a hotkey that must be set.
+    Hotkey,
+    /// A key code for an optional hotkey, or -1 for none.
+    OptionalHotkey,
+    /// A key code for a modifier, or -1 for none.
+    Modifier,
+    /// A whole number in this range, inclusive.
+    Whole(u32, u32),
+    /// A decimal number in this range, inclusive.
+    Decimal(f32, f32),
+    /// A long-press binding, or nothing.
+    LongPress,
+    /// A log level name.
+    LogLevel,
+    /// An anchor number, or `none`.
+    Anchor,
+    /// The four-letter cosave identifier.
+    Identifier,
+    /// Anything at all.
+    Text,
+}
+
+/// Every setting in the Controls and Options sections, with its rule.
+const KNOWN_SETTINGS: &[(&str, &str, Rule)] = &[
+    ("Controls", "uShowHideKey", Rule::Hotkey),
+    ("Controls", "uPowerCycleKey", Rule::Hotkey),
+    ("Controls", "uUtilityActivateKey", Rule::Hotkey),
+    ("Controls", "uLeftCycleKey", Rule::Hotkey),
+    ("Controls", "uUtilityCycleKey", Rule::Hotkey),
+    ("Controls", "uRightCycleKey", Rule::Hotkey),
+    ("Controls", "uRefreshKey", Rule::Hotkey),
+    ("Controls", "uHowToCycle", Rule::Whole(0, 3)),
+    ("Controls", "iCycleModifierKey", Rule::Modifier),
+    ("Controls", "iReverseModifierKey", Rule::Modifier),
+    ("Controls", "uHowToggleInMenus", Rule::Whole(0, 3)),
+    ("Controls", "iMenuModifierKey", Rule::Modifier),
+    ("Controls", "uHowToActivate", Rule::Whole(0, 3)),
+    ("Controls", "iUtilityActivateModifier", Rule::Modifier),
+    ("Controls", "uHowToUnequip", Rule::Whole(0, 5)),
+    ("Controls", "iUnequipModifierKey", Rule::Modifier),
+    ("Controls", "iUnequipHotkey", Rule::OptionalHotkey),
+    ("Controls", "iUndoKey", Rule::OptionalHotkey),
+    ("Controls", "iRedoKey", Rule::OptionalHotkey),
+    ("Controls", "iMacroRecordKey", Rule::OptionalHotkey),
+    ("Controls", "bLongPressMatches", Rule::Flag),
+    ("Controls", "iEquipSetCycleKey", Rule::OptionalHotkey),
+    ("Controls", "iEquipSetSwapKey", Rule::OptionalHotkey),
+    ("Controls", "bLongPressSwapsEquipSet", Rule::Flag),
+    ("Controls", "sLongPressPower", Rule::LongPress),
+    ("Controls", "sLongPressUtility", Rule::LongPress),
+    ("Controls", "sLongPressLeft", Rule::LongPress),
+    ("Controls", "sLongPressRight", Rule::LongPress),
+    ("Controls", "sLongPressEquipSet", Rule::LongPress),
+    ("Options", "uEquipDelay", Rule::Whole(0, 2500)),
+    ("Options", "uLongPressMillis", Rule::Whole(0, 10000)),
+    ("Options", "uKeySequenceMillis", Rule::Whole(50, 5000)),
+    ("Options", "uDoubleTapMillis", Rule::Whole(50, 2000)),
+    ("Options", "uMacroStepMillis", Rule::Whole(0, 10000)),
+    ("Options", "bWatchFiles", Rule::Flag),
+    ("Options", "uWatchDebounceMillis", Rule::Whole(50, 10000)),
+    ("Options", "uPinnedReturnMillis", Rule::Whole(0, 600000)),
+    ("Options", "uTriggerCooldownMillis", Rule::Whole(0, 600000)),
+    ("Options", "uMaxCycleLength", Rule::Whole(1, 100)),
+    ("Options", "uCycleOverflow", Rule::Whole(0, 2)),
+    ("Options", "bAutoFade", Rule::Flag),
+    ("Options", "uFadeTime", Rule::Whole(0, 60000)),
+    ("Options", "fMaxAlpha", Rule::Decimal(0.0, 1.0)),
+    ("Options", "fMinAlpha", Rule::Decimal(0.0, 1.0)),
+    ("Options", "uControllerKind", Rule::Whole(0, 1)),
+    ("Options", "bCyclingSlowsTime", Rule::Flag),
+    ("Options", "uSlowTimeFactor", Rule::Whole(0, 100)),
+    ("Options", "bLinkToFavorites", Rule::Flag),
+    ("Options", "bGroupPotions", Rule::Flag),
+    ("Options", "bCycleAmmo", Rule::Flag),
+    ("Options", "bSkipEmptyUtilities", Rule::Flag),
+    ("Options", "bSkipUnaffordableSpells", Rule::Flag),
+    ("Options", "bSkipLostItems", Rule::Flag),
+    ("Options", "bColorizeIcons", Rule::Flag),
+    ("Options", "bEquipSetsUnequip", Rule::Flag),
+    ("Options", "uAnchorLocation", Rule::Anchor),
+    ("Options", "fHudScale", Rule::Decimal(0.0, 10.0)),
+    ("Options", "sSKSEIdentifier", Rule::Identifier),
+    ("Options", "sTemplateTag", Rule::Text),
+    ("Options", "bDebugMode", Rule::Flag),
+    ("Options", "sLogLevel", Rule::LogLevel),
+];
+
+/// One thing wrong with the settings file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SettingsProblem {
+    /// A section we don't read.
+    UnknownSection(String),
+    /// A key we don't read, in a section we do.
+    UnknownKey { section: String, key: String },
+    /// A value that won't parse or is out of range. The reason says what we expected.
+    BadValue {
+        section: String,
+        key: String,
+        value: String,
+        reason: String,
+    },
+}
+
+impl Display for SettingsProblem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SettingsProblem::UnknownSection(section) => {
+                write!(f, "[{section}] is not a section SoulsyHUD reads.")
+            }
+            SettingsProblem::UnknownKey { section, key } => {
+                write!(f, "[{section}] {key} is not a setting SoulsyHUD knows.")
+            }
+            SettingsProblem::BadValue {
+                section,
+                key,
+                value,
+                reason,
+            } => write!(f, "[{section}] {key} = '{value}': {reason}."),
+        }
+    }
+}
+
+/// Everything wrong with the settings file, as of its last read.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SettingsReport {
+    problems: Vec<SettingsProblem>,
+}
+
+impl SettingsReport {
+    /// Look over a freshly-loaded settings file.
+    pub fn check(conf: &Ini) -> Self {
+        let mut problems = Vec::new();
+
+        for (name, properties) in conf.iter() {
+            let Some(section) = name else {
+                continue;
+            };
+            let known_section = FREEFORM_SECTIONS.contains(&section)
+                || KNOWN_SETTINGS.iter().any(|(s, _, _)| *s == section);
+            if !known_section {
+                problems.push(SettingsProblem::UnknownSection(section.to_string()));
+                continue;
+            }
+
+            for (key, value) in properties.iter() {
+                let value = value.trim();
+                if matches!(section, "Quickslots" | "Macros") {
+                    if let Err(reason) = check_key_code(key.trim(), false) {
+                        problems.push(bad_value(section, key, key, reason));
+                    }
+                    if section == "Quickslots" && !value.is_empty() {
+                        if let Err(e) = value.parse::<QuickslotTarget>() {
+                            problems.push(bad_value(section, key, value, format!("{e}")));
+                        }
+                    }
+                    continue;
+                }
+                if FREEFORM_SECTIONS.contains(&section) || RETIRED_KEYS.contains(&key) {
+                    continue;
+                }
+
+                let Some((_, _, rule)) = KNOWN_SETTINGS
+                    .iter()
+                    .find(|(s, k, _)| *s == section && *k == key)
+                else {
+                    problems.push(SettingsProblem::UnknownKey {
+                        section: section.to_string(),
+                        key: key.to_string(),
+                    });
+                    continue;
+                };
+                if let Err(reason) = check_value(*rule, value) {
+                    problems.push(bad_value(section, key, value, reason));
+                }
+            }
+        }
+
+        Self { problems }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.problems.is_empty()
+    }
+
+    pub fn problems(&self) -> &[SettingsProblem] {
+        &self.problems
+    }
+
+    /// One line per problem, for Papyrus.
+    pub fn lines(&self) -> Vec<String> {
+        self.problems.iter().map(|xs| xs.to_string()).collect()
+    }
+
+    /// Log every problem.
+    pub fn log(&self) {
+        if self.is_empty() {
+            log::debug!("The settings file passed validation.");
+            return;
+        }
+        log::warn!(
+            "Found {} problems in the settings file:",
+            self.problems.len()
+        );
+        for problem in self.problems.iter() {
+            log::warn!("    {problem}");
+        }
+    }
+}
+
+fn bad_value(section: &str, key: &str, value: &str, reason: String) -> SettingsProblem {
+    SettingsProblem::BadValue {
+        section: section.to_string(),
+        key: key.to_string(),
+        value: value.to_string(),
+        reason,
+    }
+}
+
+/// Check a key code. An optional key may be -1 for none.
+fn check_key_code(value: &str, optional: bool) -> Result<(), String> {
+    let expected = if optional {
+        format!("expected a key code from 1 to {MAX_KEY_CODE}, or -1 for none")
+    } else {
+        format!("expected a key code from 1 to {MAX_KEY_CODE}")
+    };
+    match value.parse::<i64>() {
+        Ok(-1) if optional => Ok(()),
+        Ok(code) if (1..=MAX_KEY_CODE as i64).contains(&code) => Ok(()),
+        _ => Err(expected),
+    }
+}
+
+/// Check one value against its rule.
+fn check_value(rule: Rule, value: &str) -> Result<(), String> {
+    match rule {
+        Rule::Flag => match value.to_lowercase().as_str() {
+            "0" | "1" | "true" | "false" => Ok(()),
+            _ => Err("expected 0 or 1".to_string()),
+        },
+        Rule::Hotkey => check_key_code(value, false),
+        Rule::OptionalHotkey | Rule::Modifier => check_key_code(value, true),
+        Rule::Whole(min, max) => match value.parse::<u32>() {
+            Ok(v) if (min..=max).contains(&v) => Ok(()),
+            _ => Err(format!("expected a whole number from {min} to {max}")),
+        },
+        Rule::Decimal(min, max) => match value.parse::<f32>() {
+            Ok(v) if (min..=max).contains(&v) => Ok(()),
+            _ => Err(format!("expected a number from {min} to {max}")),
+        },
+        Rule::LongPress => {
+            if value.is_empty() {
+                Ok(())
+            } else {
+                value
+                    .parse::<RequestedAction>()
+                    .map(|_| ())
+                    .map_err(|e| format!("{e}"))
+            }
+        }
+        Rule::LogLevel => value
+            .parse::<Level>()
+            .map(|_| ())
+            .map_err(|_| "expected trace, debug, info, warn, or error".to_string()),
+        Rule::Anchor => match value.parse::<u32>() {
+            Ok(v) if v <= 9 => Ok(()),
+            Err(_) if value.eq_ignore_ascii_case("none") => Ok(()),
+            _ => Err("expected an anchor number from 0 to 9, or none".to_string()),
+        },
+        Rule::Identifier => {
+            if value.is_ascii() && (1..=4).contains(&value.len()) {
+                Ok(())
+            } else {
+                Err("expected four plain letters".to_string())
+            }
+        }
+        Rule::Text => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shipped_settings_are_valid() {
+        let conf = Ini::load_from_file("./installer/core/mcm/config/SoulsyHUD/settings.ini")
+            .expect("the shipped settings file should be readable");
+        let report = SettingsReport::check(&conf);
+        assert!(report.is_empty(), "{:?}", report.lines());
+    }
+
+    #[test]
+    fn finding_problems() {
+        let text = r#"
+[Controls]
+uPowerCycleKey = 3
+uRefreshKey = 3
+uLeftCycleKey = 999
+iCycleModifierKey = 3
+uPowerCycleKy = 4
+bLongPressMatches = yes
+sLongPressLeft = dance
+
+[Options]
+fMaxAlpha = 2.0
+sSKSEIdentifier = SOULSY
+uLongPressTime = 1000
+
+[Quickslots]
+59 = utility:0
+60 = feet:2
+abc =
+
+[Mystery]
+sWhat = ever
+"#;
+        let conf = Ini::load_from_str(text).expect("the test text is valid ini");
+        let report = SettingsReport::check(&conf);
+        let problems = report.problems();
+
+        assert!(problems.contains(&SettingsProblem::UnknownSection("Mystery".to_string())));
+        assert!(problems.contains(&SettingsProblem::UnknownKey {
+            section: "Controls".to_string(),
+            key: "uPowerCycleKy".to_string(),
+        }));
+        let bad: Vec<&str> = problems
+            .iter()
+            .filter_map(|xs| match xs {
+                SettingsProblem::BadValue { key, .. } => Some(key.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            bad,
+            vec![
+                "uLeftCycleKey",
+                "bLongPressMatches",
+                "sLongPressLeft",
+                "fMaxAlpha",
+                "sSKSEIdentifier",
+                "60",
+                "abc"
+            ]
+        );
+        // Keys bound more than once are the conflict check's business.
+        assert_eq!(problems.len(), 9);
+    }
+}
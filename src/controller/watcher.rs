@@ -0,0 +1,218 @@
+//! Watch the settings and layout files for edits made outside the game.
+//!
+//! Layout authors edit TOML in a text editor and want to see the result
+//! without a trip through MCM. With `bWatchFiles` on, the renderer asks us
+//! every so often whether the settings file, the character's overrides, or
+//! the active layout file changed, and reloads only what did. Editors often
+//! write a file in several steps, so we wait until the files have sat
+//! unchanged for `uWatchDebounceMillis` before saying yes. This is polling,
+//! not OS events: it runs on the game's thread, so the reload that follows is
+//! safe.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime};
+
+use once_cell::sync::Lazy;
+
+use super::settings::settings;
+use crate::layouts::LAYOUT_PATH;
+
+/// None while watching is off, so turning it on starts from a clean slate.
+static WATCHER: Lazy<Mutex<Option<FileWatcher>>> = Lazy::new(|| Mutex::new(None));
+
+/// The modification times of a set of files, and when one last changed.
+#[derive(Debug, Clone, Default)]
+pub struct FileWatcher {
+    files: Vec<(PathBuf, Option<SystemTime>)>,
+    changed_at: Option<Instant>,
+    /// Files that changed since we last reported, waiting to settle.
+    pending: Vec<PathBuf>,
+}
+
+/// Which of the watched files changed, so the caller reloads only those.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WatchedChanges {
+    pub settings: bool,
+    pub layout: bool,
+}
+
+impl WatchedChanges {
+    /// Sort changed files into settings and layout. Anything that isn't the
+    /// layout file is a settings file.
+    pub fn from_paths(changed: &[PathBuf], layout: &Path) -> Self {
+        WatchedChanges {
+            settings: changed.iter().any(|path| path != layout),
+            layout: changed.iter().any(|path| path == layout),
+        }
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|meta| meta.modified()).ok()
+}
+
+impl FileWatcher {
+    pub fn new(paths: Vec<PathBuf>) -> Self {
+        let mut watcher = FileWatcher::default();
+        watcher.watch(paths);
+        watcher
+    }
+
+    /// Watch exactly these files. Files we already knew keep their last
+    /// seen times, so swapping the list doesn't look like a change.
+    pub fn watch(&mut self, paths: Vec<PathBuf>) {
+        self.files = paths
+            .into_iter()
+            .map(|path| {
+                let seen = self
+                    .files
+                    .iter()
+                    .find(|(known, _)| *known == path)
+                    .map_or_else(|| modified(&path), |(_, seen)| *seen);
+                (path, seen)
+            })
+            .collect();
+    }
+
+    /// Look for changes. Returns the files that changed once they have been
+    /// quiet for `debounce` since the last change, and nothing otherwise.
+    pub fn poll(&mut self, now: Instant, debounce: Duration) -> Vec<PathBuf> {
+        let mut changed = false;
+        for (path, seen) in self.files.iter_mut() {
+            let current = modified(path);
+            if current != *seen {
+                log::debug!("{} changed on disk.", path.display());
+                *seen = current;
+                changed = true;
+                if !self.pending.contains(path) {
+                    self.pending.push(path.clone());
+                }
+            }
+        }
+        if changed {
+            self.changed_at = Some(now);
+            return Vec::new();
+        }
+
+        match self.changed_at {
+            Some(at) if now.duration_since(at) >= debounce => {
+                self.changed_at = None;
+                std::mem::take(&mut self.pending)
+            }
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// Which watched files changed and settled since we last said so? Always
+/// nothing while `bWatchFiles` is off.
+pub fn files_changed() -> WatchedChanges {
+    let options = settings();
+    let mut watcher = WATCHER
+        .lock()
+        .expect("Unrecoverable runtime problem: cannot acquire file watcher lock.");
+    if !options.watch_files() {
+        *watcher = None;
+        return WatchedChanges::default();
+    }
+
+    let layout = PathBuf::from(LAYOUT_PATH);
+    let mut paths = options.source_files();
+    paths.push(layout.clone());
+    let watcher = watcher.get_or_insert_with(|| {
+        log::info!("Watching settings and layout files for changes.");
+        FileWatcher::default()
+    });
+    watcher.watch(paths);
+    let changed = watcher.poll(
+        Instant::now(),
+        Duration::from_millis(options.watch_debounce_ms() as u64),
+    );
+    WatchedChanges::from_paths(&changed, &layout)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn changes_are_debounced() {
+        let path = std::env::temp_dir().join(format!("soulsy-watch-{}.ini", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let debounce = Duration::from_millis(500);
+        let start = Instant::now();
+
+        let mut watcher = FileWatcher::new(vec![path.clone()]);
+        assert!(watcher.poll(start, debounce).is_empty());
+
+        fs::write(&path, "[Options]\n").expect("the temp dir should be writable");
+        // The change is seen, but we wait for the file to settle.
+        assert!(watcher.poll(start, debounce).is_empty());
+        assert!(watcher
+            .poll(start + Duration::from_millis(100), debounce)
+            .is_empty());
+        assert_eq!(
+            watcher.poll(start + Duration::from_millis(600), debounce),
+            vec![path.clone()]
+        );
+        // Only once per change.
+        assert!(watcher
+            .poll(start + Duration::from_millis(1200), debounce)
+            .is_empty());
+
+        // Re-watching a known file isn't a change.
+        watcher.watch(vec![path.clone()]);
+        assert!(watcher
+            .poll(start + Duration::from_millis(2000), debounce)
+            .is_empty());
+
+        fs::remove_file(&path).expect("the temp file should be removable");
+        assert!(watcher
+            .poll(start + Duration::from_millis(2100), debounce)
+            .is_empty());
+        assert_eq!(
+            watcher.poll(start + Duration::from_millis(2700), debounce),
+            vec![path]
+        );
+    }
+
+    #[test]
+    fn layout_changes_are_told_apart() {
+        let dir = std::env::temp_dir();
+        let settings = dir.join(format!("soulsy-watch-{}-settings.ini", std::process::id()));
+        let layout = dir.join(format!("soulsy-watch-{}-layout.toml", std::process::id()));
+        fs::write(&settings, "[Options]\n").expect("the temp dir should be writable");
+        let _ = fs::remove_file(&layout);
+        let debounce = Duration::from_millis(500);
+        let start = Instant::now();
+
+        let mut watcher = FileWatcher::new(vec![settings.clone(), layout.clone()]);
+        fs::write(&layout, "global_scale = 1.0\n").expect("the temp dir should be writable");
+        assert!(watcher.poll(start, debounce).is_empty());
+        let changed = watcher.poll(start + Duration::from_millis(600), debounce);
+        assert_eq!(
+            WatchedChanges::from_paths(&changed, &layout),
+            WatchedChanges {
+                settings: false,
+                layout: true,
+            }
+        );
+
+        // Removing a file is a change, too, whatever the clock resolution.
+        fs::remove_file(&settings).expect("the temp file should be removable");
+        fs::remove_file(&layout).expect("the temp file should be removable");
+        assert!(watcher
+            .poll(start + Duration::from_millis(1000), debounce)
+            .is_empty());
+        let changed = watcher.poll(start + Duration::from_millis(1600), debounce);
+        assert_eq!(
+            WatchedChanges::from_paths(&changed, &layout),
+            WatchedChanges {
+                settings: true,
+                layout: true,
+            }
+        );
+    }
+}
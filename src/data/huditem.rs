@@ -4,6 +4,7 @@ use std::fmt::Display;
 use strfmt::strfmt;
 
 use super::base::BaseType;
+use super::color::InvColor;
 use super::HasIcon;
 use crate::images::icons::Icon;
 #[cfg(not(test))]
@@ -30,6 +31,8 @@ pub struct HudItem {
     shout_cooldown: f32,
     /// Meter level, if relevant. As a percentage.
     meter_level: f32,
+    /// True for an equipment set the player can't fully equip right now.
+    broken: bool,
 }
 
 /// This is the item extra data the hud cares about and displays (full name
@@ -247,7 +250,21 @@ impl HudItem {
     }
 
     pub fn color(&self) -> Color {
-        self.kind.color()
+        if self.broken {
+            InvColor::Blood.color()
+        } else {
+            self.kind.color()
+        }
+    }
+
+    /// Flag an equipment set as missing some of its items, so the HUD can
+    /// tint its icon.
+    pub fn mark_broken(&mut self) {
+        self.broken = true;
+    }
+
+    pub fn is_broken(&self) -> bool {
+        self.broken
     }
 
     pub fn kind(&self) -> &BaseType {
@@ -79,6 +79,10 @@ impl SpellType {
         self.data.twohanded
     }
 
+    pub fn school(&self) -> &School {
+        &self.data.school
+    }
+
     pub fn icon_fallback(&self) -> Icon {
         match self.data.school {
             School::Alteration => Icon::Alteration,
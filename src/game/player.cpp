@@ -38,6 +38,45 @@ namespace player
 		return false;
 	}
 
+	rust::String playerRaceName()
+	{
+		const auto* race = RE::PlayerCharacter::GetSingleton()->GetRace();
+		if (!race) { return rust::String(); }
+		const auto* editorID = race->GetFormEditorID();
+		if (editorID && std::strlen(editorID) > 0) { return rust::String(editorID); }
+		// Without an editor id we fall back to the display name, e.g. "Nord".
+		return rust::String(race->GetName());
+	}
+
+	rust::String playerName()
+	{
+		const auto* player = RE::PlayerCharacter::GetSingleton();
+		if (!player) { return rust::String(); }
+		const auto* name = player->GetName();
+		if (!name) { return rust::String(); }
+		return rust::String(name);
+	}
+
+	rust::Vec<rust::String> currentLocationKeywords()
+	{
+		auto keywords = rust::Vec<rust::String>();
+		auto* player  = RE::PlayerCharacter::GetSingleton();
+		if (!player) { return keywords; }
+
+		// Walk up from the current location so a dungeon inside a hold matches both.
+		for (auto* location = player->GetCurrentLocation(); location; location = location->parentLoc)
+		{
+			for (uint32_t i = 0; i < location->GetNumKeywords(); i++)
+			{
+				const auto keyword = location->GetKeywordAt(i);
+				if (!keyword || !keyword.value()) { continue; }
+				const auto* editorID = keyword.value()->GetFormEditorID();
+				if (editorID && std::strlen(editorID) > 0) { keywords.push_back(std::string(editorID)); }
+			}
+		}
+		return keywords;
+	}
+
 	bool useCGOAltGrip()
 	{
 		bool useAltGrip = false;
@@ -107,7 +146,7 @@ namespace player
 		auto player     = RE::PlayerCharacter::GetSingleton();
 		auto* rightItem = player->GetActorRuntimeData().currentProcess->GetEquippedRightHand();
 		bool useBolts   = false;
-		if (rightItem->IsWeapon())
+		if (rightItem && rightItem->IsWeapon())
 		{
 			auto* weapon = rightItem->As<RE::TESObjectWEAP>();
 			useBolts     = weapon->IsCrossbow();
@@ -116,7 +155,7 @@ namespace player
 		{
 			// filter for the same type that we have equipped
 			auto* currentAmmo = player->GetCurrentAmmo();
-			useBolts          = currentAmmo->IsBolt();
+			useBolts          = currentAmmo && currentAmmo->IsBolt();
 		}
 
 		auto ammoTypes = getInventoryForType(player, RE::FormType::Ammo);
@@ -140,6 +179,79 @@ namespace player
 		return std::move(*specs);
 	}
 
+	rust::Vec<rust::String> getInventoryFormSpecs()
+	{
+		auto* player = RE::PlayerCharacter::GetSingleton();
+		auto specs   = rust::Vec<rust::String>();
+		auto record  = [&specs](const RE::TESForm* form) {
+			if (form) { specs.push_back(rust::String(helpers::makeFormSpecString(form))); }
+		};
+
+		const auto formTypes = { RE::FormType::AlchemyItem, RE::FormType::Ammo,   RE::FormType::Armor,
+			RE::FormType::Light,                              RE::FormType::Scroll, RE::FormType::Weapon };
+		for (const auto formType : formTypes)
+		{
+			for (const auto& [item, inv_data] : getInventoryForType(player, formType))
+			{
+				const auto& [num_items, entry] = inv_data;
+				if (num_items > 0) { record(item); }
+			}
+		}
+
+		for (const auto* spell : player->GetActorRuntimeData().addedSpells) { record(spell); }
+		if (const auto* spellData = player->GetActorBase()->GetSpellList())
+		{
+			for (uint32_t i = 0; i < spellData->numSpells; ++i) { record(spellData->spells[i]); }
+			for (uint32_t i = 0; i < spellData->numShouts; ++i) { record(spellData->shouts[i]); }
+		}
+
+		rlog::debug("inventory scan found {} candidate forms"sv, specs.size());
+		return specs;
+	}
+
+	float formStrength(const std::string& form_spec)
+	{
+		auto* form = helpers::formSpecToFormItem(form_spec);
+		if (!form) { return 0.0f; }
+
+		if (auto* magic = form->As<RE::MagicItem>())
+		{
+			const auto* effect = magic->GetCostliestEffectItem();
+			return effect ? effect->GetMagnitude() : 0.0f;
+		}
+		if (const auto* weapon = form->As<RE::TESObjectWEAP>())
+		{
+			return static_cast<float>(weapon->GetAttackDamage());
+		}
+		if (const auto* ammo = form->As<RE::TESAmmo>()) { return ammo->GetRuntimeData().data.damage; }
+		if (const auto* armor = form->As<RE::TESObjectARMO>()) { return armor->GetArmorRating(); }
+
+		return 0.0f;
+	}
+
+	uint32_t armorSlotMask(const std::string& form_spec)
+	{
+		auto* form = helpers::formSpecToFormItem(form_spec);
+		if (!form) { return 0; }
+		const auto* armor = form->As<RE::TESObjectARMO>();
+		if (!armor) { return 0; }
+		return static_cast<uint32_t>(armor->GetSlotMask());
+	}
+
+	bool canAffordSpell(const std::string& form_spec)
+	{
+		auto* form = helpers::formSpecToFormItem(form_spec);
+		if (!form || !form->Is(RE::FormType::Spell)) { return true; }
+		auto* spell = form->As<RE::SpellItem>();
+
+		auto* player       = RE::PlayerCharacter::GetSingleton();
+		const auto cost    = spell->CalculateMagickaCost(player);
+		const auto magicka = player->AsActorValueOwner()->GetActorValue(RE::ActorValue::kMagicka);
+		rlog::trace("spell cost check: name='{}'; cost={}; magicka={};"sv, helpers::nameAsUtf8(form), cost, magicka);
+
+		return cost <= magicka;
+	}
+
 	bool hasRangedEquipped()
 	{
 		auto player    = RE::PlayerCharacter::GetSingleton();
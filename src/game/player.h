@@ -21,6 +21,9 @@ namespace player
 	rust::String specEquippedPower();
 	rust::String specEquippedAmmo();
 	rust::Vec<rust::String> getAmmoInventory();
+	rust::Vec<rust::String> getInventoryFormSpecs();
+	float formStrength(const std::string& form_spec);
+	uint32_t armorSlotMask(const std::string& form_spec);
 	bool compare(RE::TESAmmo* left, RE::TESAmmo* right);
 
 	rust::Box<EquippedData> getEquippedItems();
@@ -30,6 +33,9 @@ namespace player
 	bool hasRangedEquipped();
 	bool isVampireLord();
 	bool isWerewolf();
+	rust::String playerRaceName();
+	rust::String playerName();
+	rust::Vec<rust::String> currentLocationKeywords();
 
 	void unequipSlot(Action slot);
 	void unequipShout();
@@ -45,6 +51,7 @@ namespace player
 	void consumePotion(const std::string& form_spec);
 
 	bool hasItemOrSpell(const std::string& form_spec);
+	bool canAffordSpell(const std::string& form_spec);
 	uint32_t itemCount(const std::string& form_spec);
 	uint32_t staminaPotionCount();
 	uint32_t healthPotionCount();
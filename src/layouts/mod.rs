@@ -20,7 +20,7 @@ use crate::controller::control::translated_key;
 use crate::controller::user_settings;
 use crate::plugin::{LayoutFlattened, Point};
 
-static LAYOUT_PATH: &str = "./data/SKSE/Plugins/SoulsyHUD_Layout.toml";
+pub static LAYOUT_PATH: &str = "./data/SKSE/Plugins/SoulsyHUD_Layout.toml";
 
 /// There can be only one. Not public because we want access managed.
 static LAYOUT: Lazy<Mutex<LayoutFlattened>> = Lazy::new(|| Mutex::new(Layout::initialize()));
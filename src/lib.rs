@@ -213,6 +213,22 @@ pub mod plugin {
         LongPressPower,
         /// Long press the powers/shouts key.
         LongPressUtility,
+        /// Undo the last cycle or equipset edit.
+        Undo,
+        /// Redo the last undone edit.
+        Redo,
+        /// Long press the equipset cycle key.
+        LongPressEquipSet,
+        /// Swap back to the previously equipped equipset.
+        EquipSetSwap,
+        /// A quickslot key: use or equip its bound item right away.
+        Quickslot,
+        /// Start or cancel recording a macro.
+        MacroRecord,
+        /// A macro key: save a recording here, or play or stop its macro.
+        Macro,
+        /// Timer for pacing the steps of a playing macro.
+        MacroStep,
     }
 
     /// A high-level item category, used to jump-start item categorization via keywords & form data.
@@ -301,6 +317,9 @@ pub mod plugin {
         /// Trigger rust to read config, figure out what the player has equipped,
         /// and figure out what it should draw.
         fn initialize_hud();
+        /// Give a character who has never used the HUD a cycle template. Called after a
+        /// save loads and when the race menu closes, never on a brand-new game.
+        fn apply_starting_template();
         /// Get cycle data for cosave.
         fn serialize_cycles() -> Vec<u8>;
         /// Serialization format version.
@@ -311,6 +330,10 @@ pub mod plugin {
         fn clear_cache();
         /// Refresh the enchant charge / time remaining / poisoned status of all visible items.
         fn refresh_hud_items();
+        /// Check the game state against equipset triggers. Called periodically by the renderer.
+        fn check_equipset_triggers();
+        /// Reload settings and layout if their files changed on disk. Called periodically by the renderer.
+        fn check_watched_files();
 
         /// Give access to the settings to the C++ side.
         type UserSettings;
@@ -366,6 +389,8 @@ pub mod plugin {
         fn fmtstr(self: &HudItem, format: String) -> String;
         /// Check if this item is poisoned.
         fn is_poisoned(self: &HudItem) -> bool;
+        /// Check if this is an equipment set with missing items.
+        fn is_broken(self: &HudItem) -> bool;
         /// Check if this item needs a meter drawn.
         fn show_meter(self: &HudItem) -> bool;
         /// Get the meter level as a percentage of full/complete.
@@ -473,6 +498,60 @@ pub mod plugin {
         fn get_cycle_names(which: i32) -> Vec<String>;
         /// Get a list of form spec strings for the given cycle. Used in MCM.
         fn get_cycle_formids(which: i32) -> Vec<String>;
+        /// Get the names of all saved cycle profiles. Used in MCM.
+        fn get_cycle_profile_names() -> Vec<String>;
+        /// Get the name of the active cycle profile, or an empty string. Used in MCM.
+        fn get_active_cycle_profile() -> String;
+        /// Save the current cycles as a new named profile. Used in MCM.
+        fn handle_create_cycle_profile(name: String) -> bool;
+        /// Swap the current cycles for the named profile's. Used in MCM.
+        fn handle_switch_cycle_profile(name: String) -> bool;
+        /// Delete a cycle profile by name. Used in MCM.
+        fn handle_remove_cycle_profile(name: String) -> bool;
+        /// Pin or unpin an entry in the given cycle. Used in MCM.
+        fn set_cycle_entry_pinned(which: i32, form_spec: String, pinned: bool) -> bool;
+        /// Set whether an entry is skipped when it can't be used. Used in MCM.
+        fn set_cycle_entry_skip_unusable(which: i32, form_spec: String, skip: bool) -> bool;
+        /// Get the form spec of the pinned entry in the given cycle, or an empty string. Used in MCM.
+        fn get_cycle_pinned(which: i32) -> String;
+        /// Get the form specs of entries skipped when unusable in the given cycle. Used in MCM.
+        fn get_cycle_skip_unusable(which: i32) -> Vec<String>;
+        /// Make the entry at the given index current in a cycle, then equip it. Used in MCM.
+        fn handle_select_cycle_index(which: i32, index: i32) -> bool;
+        /// Make the equip set at the given index current, then equip it. Used in MCM.
+        fn handle_select_equipset_index(index: i32) -> bool;
+        /// Sort a cycle by a named mode, once or kept applied. Used in MCM.
+        fn handle_sort_cycle(which: i32, mode: String, continuous: bool) -> bool;
+        /// Get the sort mode kept applied to a cycle; empty if none. Used in MCM.
+        fn get_cycle_sort_mode(which: i32) -> String;
+        /// Get the parts of the player's gear an equipset changes. Used in MCM.
+        fn get_equipset_parts(id: u32) -> Vec<String>;
+        /// Turn one part of an equipset on or off: armor, power, ammo, left, or right. Used in MCM.
+        fn handle_set_equipset_part(id: u32, part: String, enabled: bool) -> bool;
+        /// Get an equipset's triggers as names, e.g. `combat`. Used in MCM.
+        fn get_equipset_triggers(id: u32) -> Vec<String>;
+        /// Set an equipset's triggers from a comma-separated list. Used in MCM.
+        fn handle_set_equipset_triggers(id: u32, triggers: String) -> bool;
+        /// Are this equipset's triggers switched on? Used in MCM.
+        fn get_equipset_auto_equip(id: u32) -> bool;
+        /// Switch this equipset's triggers on or off. Used in MCM.
+        fn handle_set_equipset_auto_equip(id: u32, enabled: bool) -> bool;
+        /// Get the id of the equipset this one is based on, or -1 if none. Used in MCM.
+        fn get_equipset_parent(id: u32) -> i32;
+        /// Base an equipset on another, or pass -1 to make it stand alone. Used in MCM.
+        fn handle_set_equipset_parent(id: u32, parent: i32) -> bool;
+        /// Get the names of the loadout files available to import. Used in MCM.
+        fn get_loadout_names() -> Vec<String>;
+        /// Write cycles and equipsets to the named loadout file. Used in MCM.
+        fn handle_export_loadout(name: String) -> bool;
+        /// Merge in or replace with the named loadout; returns missing entry count or -1. Used in MCM.
+        fn handle_import_loadout(name: String, replace: bool) -> i32;
+        /// Get the names of the auto-fill rules in the settings file. Used in MCM.
+        fn get_auto_fill_rule_names() -> Vec<String>;
+        /// Fill a cycle using the named auto-fill rule; returns items added or -1. Used in MCM.
+        fn handle_auto_fill(name: String) -> i32;
+        /// Describe each problem found in the settings file. Used in MCM.
+        fn get_settings_problems() -> Vec<String>;
         /// Get equip set names in order by id. Used in MCM.
         fn get_equipset_names() -> Vec<String>;
         /// Get equip set ids. Used in MCM.
@@ -493,6 +572,8 @@ pub mod plugin {
         fn equipped_data(items: Vec<String>, empty: Vec<u8>) -> Box<EquippedData>;
         /// Get a vec of the names of all items in this equip set. Called by MCM.
         fn get_equipset_item_names(id: u32) -> Vec<String>;
+        /// Describe anything this equipset can't equip. Called by MCM.
+        fn get_equipset_problems(id: u32) -> Vec<String>;
         /// Set which item's icon to use for this equipset. Called by MCM.
         fn set_equipset_icon(id: u32, itemname: String) -> bool;
         /// Given the selected equipset name, get its integer id. Called by MCM.
@@ -580,6 +661,12 @@ pub mod plugin {
         fn isInCombat() -> bool;
         /// Are the player's weapons drawn?
         fn weaponsAreDrawn() -> bool;
+        /// Get the editor id of the player's race, e.g. `NordRace`. Used to pick a cycle template.
+        fn playerRaceName() -> String;
+        /// Get the player character's name. Used to find per-character settings overrides.
+        fn playerName() -> String;
+        /// Get the editor ids of the keywords on the player's location and its parent locations.
+        fn currentLocationKeywords() -> Vec<String>;
 
         /// Get the form spec for the item readied in the left hand, bound form if possible.
         fn specEquippedLeft() -> String;
@@ -592,13 +679,23 @@ pub mod plugin {
 
         /// Check if the player still has items from this form in their inventory.
         fn hasItemOrSpell(form_spec: &CxxString) -> bool;
+        /// Does the player have enough magicka to cast this spell? True for non-spells.
+        fn canAffordSpell(form_spec: &CxxString) -> bool;
 
         /// Does the player have a bow or crossbow equipped?
         fn hasRangedEquipped() -> bool;
         /// Get a vec of form specs for all relevant ammo in the player's inventory.
         /// The vec is sorted by damage.
         fn getAmmoInventory() -> Vec<String>;
-
+        /// Get form specs for everything the player carries or knows that might go
+        /// in a cycle: weapons, armor, ammo, lights, potions, food, scrolls, spells, and shouts.
+        fn getInventoryFormSpecs() -> Vec<String>;
+        /// How strong is this item? Effect magnitude for potions and spells, damage
+        /// for weapons and ammo, and armor rating for armor. Used to sort auto-filled cycles.
+        fn formStrength(form_spec: &CxxString) -> f32;
+
+        /// Get the biped slot mask for an armor form; zero for anything else.
+        fn armorSlotMask(form_spec: &CxxString) -> u32;
         /// Get a list of form specs for all equipped armor. Used to build an equipset.
         fn getEquippedItems() -> Box<EquippedData>;
 
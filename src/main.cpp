@@ -55,7 +55,11 @@ void message_callback(SKSE::MessagingInterface::Message* msg)
 			break;
 		case SKSE::MessagingInterface::kPostLoadGame:
 			rlog::trace("SKSE kPostLoadGame message received: type={};"sv, static_cast<uint32_t>(msg->type));
+			initialize_hud();
+			apply_starting_template();
+			break;
 		case SKSE::MessagingInterface::kNewGame:
+			// The player hasn't picked a race yet. Templates wait for the race menu to close.
 			rlog::trace("SKSE kNewGame message received: type={};"sv, static_cast<uint32_t>(msg->type));
 			initialize_hud();
 			break;
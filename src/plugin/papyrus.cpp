@@ -30,6 +30,7 @@ namespace papyrus
 		a_vm->RegisterFunction("GetEquipSetNames", MCM_NAME, getEquipSetNames);
 		a_vm->RegisterFunction("GetEquipSetIDs", MCM_NAME, getEquipSetIDs);
 		a_vm->RegisterFunction("GetEquipSetItemNames", MCM_NAME, getEquipSetItemNames);
+		a_vm->RegisterFunction("GetEquipSetProblems", MCM_NAME, getEquipSetProblems);
 		a_vm->RegisterFunction("SetItemAsEquipSetIcon", MCM_NAME, setItemAsEquipSetIcon);
 		a_vm->RegisterFunction("FindSelectedSetID", MCM_NAME, findSelectedSetByName);
 
@@ -38,6 +39,35 @@ namespace papyrus
 		a_vm->RegisterFunction("GetCycleNames", MCM_NAME, getCycleNames);
 		a_vm->RegisterFunction("GetCycleFormIDs", MCM_NAME, getCycleFormIDs);
 
+		a_vm->RegisterFunction("GetCycleProfileNames", MCM_NAME, getCycleProfileNames);
+		a_vm->RegisterFunction("GetActiveCycleProfile", MCM_NAME, getActiveCycleProfile);
+		a_vm->RegisterFunction("HandleCreateCycleProfile", MCM_NAME, handleCreateCycleProfile);
+		a_vm->RegisterFunction("HandleSwitchCycleProfile", MCM_NAME, handleSwitchCycleProfile);
+		a_vm->RegisterFunction("HandleRemoveCycleProfile", MCM_NAME, handleRemoveCycleProfile);
+
+		a_vm->RegisterFunction("SetCycleEntryPinned", MCM_NAME, setCycleEntryPinned);
+		a_vm->RegisterFunction("SetCycleEntrySkipUnusable", MCM_NAME, setCycleEntrySkipUnusable);
+		a_vm->RegisterFunction("GetCyclePinned", MCM_NAME, getCyclePinned);
+		a_vm->RegisterFunction("GetCycleSkipUnusable", MCM_NAME, getCycleSkipUnusable);
+		a_vm->RegisterFunction("HandleSelectCycleIndex", MCM_NAME, handleSelectCycleIndex);
+		a_vm->RegisterFunction("HandleSelectEquipSetIndex", MCM_NAME, handleSelectEquipSetIndex);
+		a_vm->RegisterFunction("HandleSortCycle", MCM_NAME, handleSortCycle);
+		a_vm->RegisterFunction("GetCycleSortMode", MCM_NAME, getCycleSortMode);
+		a_vm->RegisterFunction("GetEquipSetParts", MCM_NAME, getEquipSetParts);
+		a_vm->RegisterFunction("HandleSetEquipSetPart", MCM_NAME, handleSetEquipSetPart);
+		a_vm->RegisterFunction("GetEquipSetTriggers", MCM_NAME, getEquipSetTriggers);
+		a_vm->RegisterFunction("HandleSetEquipSetTriggers", MCM_NAME, handleSetEquipSetTriggers);
+		a_vm->RegisterFunction("GetEquipSetAutoEquip", MCM_NAME, getEquipSetAutoEquip);
+		a_vm->RegisterFunction("HandleSetEquipSetAutoEquip", MCM_NAME, handleSetEquipSetAutoEquip);
+		a_vm->RegisterFunction("GetEquipSetParent", MCM_NAME, getEquipSetParent);
+		a_vm->RegisterFunction("HandleSetEquipSetParent", MCM_NAME, handleSetEquipSetParent);
+		a_vm->RegisterFunction("GetLoadoutNames", MCM_NAME, getLoadoutNames);
+		a_vm->RegisterFunction("HandleExportLoadout", MCM_NAME, handleExportLoadout);
+		a_vm->RegisterFunction("HandleImportLoadout", MCM_NAME, handleImportLoadout);
+		a_vm->RegisterFunction("GetAutoFillRuleNames", MCM_NAME, getAutoFillRuleNames);
+		a_vm->RegisterFunction("HandleAutoFill", MCM_NAME, handleAutoFill);
+		a_vm->RegisterFunction("GetSettingsProblems", MCM_NAME, getSettingsProblems);
+
 		a_vm->RegisterFunction("GetResolutionWidth", MCM_NAME, get_resolution_width);
 		a_vm->RegisterFunction("GetResolutionHeight", MCM_NAME, get_resolution_height);
 
@@ -95,6 +125,15 @@ namespace papyrus
 		return array;
 	}
 
+	RE::BSTArray<RE::BSFixedString> getEquipSetProblems(RE::TESQuest*, uint32_t id)
+	{
+		auto problems = get_equipset_problems(id);
+		auto array    = RE::BSTArray<RE::BSFixedString>();
+		for (auto problem : problems) { array.push_back(std::string(problem)); }
+
+		return array;
+	}
+
 	bool setItemAsEquipSetIcon(RE::TESQuest*, uint32_t id, RE::BSFixedString fixed)
 	{
 		return set_equipset_icon(id, std::string(fixed));
@@ -137,6 +176,172 @@ namespace papyrus
 		return array;
 	}
 
+	RE::BSTArray<RE::BSFixedString> getCycleProfileNames(RE::TESQuest*)
+	{
+		auto names = get_cycle_profile_names();
+		auto array = RE::BSTArray<RE::BSFixedString>();
+		for (auto name : names) { array.push_back(std::string(name)); }
+
+		return array;
+	}
+
+	RE::BSFixedString getActiveCycleProfile(RE::TESQuest*) { return std::string(get_active_cycle_profile()); }
+
+	bool handleCreateCycleProfile(RE::TESQuest*, RE::BSFixedString fixed)
+	{
+		auto name = std::string(fixed);
+		return handle_create_cycle_profile(name);
+	}
+
+	bool handleSwitchCycleProfile(RE::TESQuest*, RE::BSFixedString fixed)
+	{
+		auto name = std::string(fixed);
+		rlog::debug("handleSwitchCycleProfile(): name='{}';", name);
+		return handle_switch_cycle_profile(name);
+	}
+
+	bool handleRemoveCycleProfile(RE::TESQuest*, RE::BSFixedString fixed)
+	{
+		auto name = std::string(fixed);
+		return handle_remove_cycle_profile(name);
+	}
+
+	bool setCycleEntryPinned(RE::TESQuest*, int which, RE::BSFixedString fixed, bool pinned)
+	{
+		auto form_spec = std::string(fixed);
+		return set_cycle_entry_pinned(which, form_spec, pinned);
+	}
+
+	bool setCycleEntrySkipUnusable(RE::TESQuest*, int which, RE::BSFixedString fixed, bool skip)
+	{
+		auto form_spec = std::string(fixed);
+		return set_cycle_entry_skip_unusable(which, form_spec, skip);
+	}
+
+	RE::BSFixedString getCyclePinned(RE::TESQuest*, int which) { return std::string(get_cycle_pinned(which)); }
+
+	RE::BSTArray<RE::BSFixedString> getCycleSkipUnusable(RE::TESQuest*, int which)
+	{
+		auto specs = get_cycle_skip_unusable(which);
+		auto array = RE::BSTArray<RE::BSFixedString>();
+		for (auto spec : specs) { array.push_back(std::string(spec)); }
+
+		return array;
+	}
+
+	bool handleSelectCycleIndex(RE::TESQuest*, int which, int index)
+	{
+		rlog::debug("handleSelectCycleIndex(): which={}; index={};", which, index);
+		return handle_select_cycle_index(which, index);
+	}
+
+	bool handleSelectEquipSetIndex(RE::TESQuest*, int index) { return handle_select_equipset_index(index); }
+
+	bool handleSortCycle(RE::TESQuest*, int which, RE::BSFixedString fixed, bool continuous)
+	{
+		auto mode = std::string(fixed);
+		rlog::debug("handleSortCycle(): which={}; mode='{}'; continuous={};", which, mode, continuous);
+		return handle_sort_cycle(which, mode, continuous);
+	}
+
+	RE::BSFixedString getCycleSortMode(RE::TESQuest*, int which) { return std::string(get_cycle_sort_mode(which)); }
+
+	RE::BSTArray<RE::BSFixedString> getEquipSetParts(RE::TESQuest*, uint32_t id)
+	{
+		auto parts = get_equipset_parts(id);
+		auto array = RE::BSTArray<RE::BSFixedString>();
+		for (auto part : parts) { array.push_back(std::string(part)); }
+
+		return array;
+	}
+
+	bool handleSetEquipSetPart(RE::TESQuest*, uint32_t id, RE::BSFixedString fixed, bool enabled)
+	{
+		auto part = std::string(fixed);
+		rlog::debug("handleSetEquipSetPart(): id={}; part='{}'; enabled={};", id, part, enabled);
+		return handle_set_equipset_part(id, part, enabled);
+	}
+
+	RE::BSTArray<RE::BSFixedString> getEquipSetTriggers(RE::TESQuest*, uint32_t id)
+	{
+		auto triggers = get_equipset_triggers(id);
+		auto array    = RE::BSTArray<RE::BSFixedString>();
+		for (auto trigger : triggers) { array.push_back(std::string(trigger)); }
+
+		return array;
+	}
+
+	bool handleSetEquipSetTriggers(RE::TESQuest*, uint32_t id, RE::BSFixedString fixed)
+	{
+		auto triggers = std::string(fixed);
+		rlog::debug("handleSetEquipSetTriggers(): id={}; triggers='{}';", id, triggers);
+		return handle_set_equipset_triggers(id, triggers);
+	}
+
+	bool getEquipSetAutoEquip(RE::TESQuest*, uint32_t id) { return get_equipset_auto_equip(id); }
+
+	bool handleSetEquipSetAutoEquip(RE::TESQuest*, uint32_t id, bool enabled)
+	{
+		rlog::debug("handleSetEquipSetAutoEquip(): id={}; enabled={};", id, enabled);
+		return handle_set_equipset_auto_equip(id, enabled);
+	}
+
+	int getEquipSetParent(RE::TESQuest*, uint32_t id) { return get_equipset_parent(id); }
+
+	bool handleSetEquipSetParent(RE::TESQuest*, uint32_t id, int parent)
+	{
+		rlog::debug("handleSetEquipSetParent(): id={}; parent={};", id, parent);
+		return handle_set_equipset_parent(id, parent);
+	}
+
+	RE::BSTArray<RE::BSFixedString> getLoadoutNames(RE::TESQuest*)
+	{
+		auto names = get_loadout_names();
+		auto array = RE::BSTArray<RE::BSFixedString>();
+		for (auto name : names) { array.push_back(std::string(name)); }
+
+		return array;
+	}
+
+	bool handleExportLoadout(RE::TESQuest*, RE::BSFixedString fixed)
+	{
+		auto name = std::string(fixed);
+		rlog::debug("handleExportLoadout(): name='{}';", name);
+		return handle_export_loadout(name);
+	}
+
+	int handleImportLoadout(RE::TESQuest*, RE::BSFixedString fixed, bool replace)
+	{
+		auto name = std::string(fixed);
+		rlog::debug("handleImportLoadout(): name='{}'; replace={};", name, replace);
+		return handle_import_loadout(name, replace);
+	}
+
+	RE::BSTArray<RE::BSFixedString> getAutoFillRuleNames(RE::TESQuest*)
+	{
+		auto names = get_auto_fill_rule_names();
+		auto array = RE::BSTArray<RE::BSFixedString>();
+		for (auto name : names) { array.push_back(std::string(name)); }
+
+		return array;
+	}
+
+	int handleAutoFill(RE::TESQuest*, RE::BSFixedString fixed)
+	{
+		auto name = std::string(fixed);
+		rlog::debug("handleAutoFill(): name='{}';", name);
+		return handle_auto_fill(name);
+	}
+
+	RE::BSTArray<RE::BSFixedString> getSettingsProblems(RE::TESQuest*)
+	{
+		auto problems = get_settings_problems();
+		auto array    = RE::BSTArray<RE::BSFixedString>();
+		for (auto problem : problems) { array.push_back(std::string(problem)); }
+
+		return array;
+	}
+
 	RE::BSFixedString get_resolution_width(RE::TESQuest*)
 	{
 		return fmt::format(FMT_STRING("{:.2f}"), ui::resolutionWidth());
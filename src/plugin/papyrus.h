@@ -8,6 +8,35 @@ namespace papyrus
 	RE::BSTArray<RE::BSFixedString> getCycleNames(RE::TESQuest*, int which);
 	RE::BSTArray<RE::BSFixedString> getCycleFormIDs(RE::TESQuest*, int which);
 
+	RE::BSTArray<RE::BSFixedString> getCycleProfileNames(RE::TESQuest*);
+	RE::BSFixedString getActiveCycleProfile(RE::TESQuest*);
+	bool handleCreateCycleProfile(RE::TESQuest*, RE::BSFixedString name);
+	bool handleSwitchCycleProfile(RE::TESQuest*, RE::BSFixedString name);
+	bool handleRemoveCycleProfile(RE::TESQuest*, RE::BSFixedString name);
+
+	bool setCycleEntryPinned(RE::TESQuest*, int which, RE::BSFixedString form_spec, bool pinned);
+	bool setCycleEntrySkipUnusable(RE::TESQuest*, int which, RE::BSFixedString form_spec, bool skip);
+	RE::BSFixedString getCyclePinned(RE::TESQuest*, int which);
+	RE::BSTArray<RE::BSFixedString> getCycleSkipUnusable(RE::TESQuest*, int which);
+	bool handleSelectCycleIndex(RE::TESQuest*, int which, int index);
+	bool handleSelectEquipSetIndex(RE::TESQuest*, int index);
+	bool handleSortCycle(RE::TESQuest*, int which, RE::BSFixedString mode, bool continuous);
+	RE::BSFixedString getCycleSortMode(RE::TESQuest*, int which);
+	RE::BSTArray<RE::BSFixedString> getEquipSetParts(RE::TESQuest*, uint32_t id);
+	bool handleSetEquipSetPart(RE::TESQuest*, uint32_t id, RE::BSFixedString part, bool enabled);
+	RE::BSTArray<RE::BSFixedString> getEquipSetTriggers(RE::TESQuest*, uint32_t id);
+	bool handleSetEquipSetTriggers(RE::TESQuest*, uint32_t id, RE::BSFixedString triggers);
+	bool getEquipSetAutoEquip(RE::TESQuest*, uint32_t id);
+	bool handleSetEquipSetAutoEquip(RE::TESQuest*, uint32_t id, bool enabled);
+	int getEquipSetParent(RE::TESQuest*, uint32_t id);
+	bool handleSetEquipSetParent(RE::TESQuest*, uint32_t id, int parent);
+	RE::BSTArray<RE::BSFixedString> getLoadoutNames(RE::TESQuest*);
+	bool handleExportLoadout(RE::TESQuest*, RE::BSFixedString name);
+	int handleImportLoadout(RE::TESQuest*, RE::BSFixedString name, bool replace);
+	RE::BSTArray<RE::BSFixedString> getAutoFillRuleNames(RE::TESQuest*);
+	int handleAutoFill(RE::TESQuest*, RE::BSFixedString name);
+	RE::BSTArray<RE::BSFixedString> getSettingsProblems(RE::TESQuest*);
+
 	RE::BSFixedString get_resolution_width(RE::TESQuest*);
 	RE::BSFixedString get_resolution_height(RE::TESQuest*);
 
@@ -21,6 +50,7 @@ namespace papyrus
 	RE::BSTArray<RE::BSFixedString> getEquipSetIDs(RE::TESQuest*);
 	int findSelectedSetByName(RE::TESQuest*, RE::BSFixedString name);
 	RE::BSTArray<RE::BSFixedString> getEquipSetItemNames(RE::TESQuest*, uint32_t id);
+	RE::BSTArray<RE::BSFixedString> getEquipSetProblems(RE::TESQuest*, uint32_t id);
 	bool setItemAsEquipSetIcon(RE::TESQuest*, uint32_t id, RE::BSFixedString fixed);
 
 	bool Register(RE::BSScript::IVirtualMachine* a_vm);
@@ -122,6 +122,8 @@ RE::BSEventNotifyControl TheListener::ProcessEvent(const RE::MenuOpenCloseEvent*
 	const auto menu = std::string(event->menuName);
 	if (NO_SHOW_MENUS.contains(menu)) { helpers::setNoShowMenuOpen(event->opening); }
 	if (NO_INPUT_MENUS.contains(menu)) { helpers::setNoInputMenuOpen(event->opening); }
+	// A new character has a race and starting gear once the race menu closes.
+	if (menu == RE::RaceSexMenu::MENU_NAME && !event->opening) { apply_starting_template(); }
 	return RE::BSEventNotifyControl::kContinue;
 }
 
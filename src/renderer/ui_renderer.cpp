@@ -40,6 +40,7 @@ namespace ui
 	static std::map<std::string, TextureData> HUD_IMAGES_MAP;
 
 	static const auto REFRESH_DRAW_COUNT  = 50;
+	static const auto TRIGGER_CHECK_COUNT = 15;
 	static const float FADEOUT_HYSTERESIS = 0.5f;  // seconds
 	static const uint32_t MAX_ICON_DIM    = 300;   // rasterized at 96 dpi
 	static constexpr ImVec2 FLAT_UVS[4]   = { ImVec2(0.0f, 0.0f),
@@ -59,6 +60,7 @@ namespace ui
 	auto delayBeforeFadeout = 0.33f;  // seconds
 	bool gDoingBriefPeek    = false;
 	auto drawCounter        = 0;
+	auto triggerCounter     = 0;
 
 	// ID3D11BlendState* gBlendState = nullptr;
 
@@ -628,7 +630,7 @@ namespace ui
 			// now draw the icon over the background...
 			if (slotLayout.icon_color.a > 0 && !skipItem)
 			{
-				const auto iconColor = colorizeIcons ? entry->color() : slotLayout.icon_color;
+				const auto iconColor = (colorizeIcons || entry->is_broken()) ? entry->color() : slotLayout.icon_color;
 				auto iconkey         = std::string(entry->icon_key());
 				if (ui_renderer::lazyLoadIcon(iconkey))
 				{
@@ -705,6 +707,16 @@ namespace ui
 		advanceTimers(timeDelta);
 
 		if (!helpers::hudAllowedOnScreen()) return;
+
+		// Equipset triggers and watched files are checked even while the HUD is faded out.
+		if (triggerCounter >= TRIGGER_CHECK_COUNT)
+		{
+			check_equipset_triggers();
+			check_watched_files();
+			triggerCounter = 0;
+		}
+		triggerCounter++;
+
 		makeFadeDecision();
 		advanceTransition(timeDelta);
 		if (gHudAlpha == 0.0f) { return; }